@@ -0,0 +1,13 @@
+-- this records how far a backfill over an explicit slot range has progressed so it can be resumed
+
+USE solana;
+
+CREATE TABLE IF NOT EXISTS backfill_progress
+(
+    start_slot UInt64,
+    end_slot UInt64,
+    last_slot UInt64,                  -- the last slot in the range that has been handled
+    updated_at DateTime DEFAULT now()
+)
+ENGINE = ReplacingMergeTree(last_slot)
+ORDER BY (start_slot, end_slot);
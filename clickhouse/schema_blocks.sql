@@ -0,0 +1,18 @@
+-- this stores one row per fetched block; the analysis tables reference it by slot
+
+USE solana;
+
+CREATE TABLE IF NOT EXISTS blocks
+(
+    slot UInt64,
+    parent_slot UInt64,
+    blockhash String,
+    previous_blockhash String,
+    block_time UInt64,
+    transaction_count UInt32,
+    created_at DateTime DEFAULT now(),
+    ingest_version UInt64 DEFAULT toUnixTimestamp64Milli(now64(3))  -- the latest write of a slot wins when rows are merged
+)
+ENGINE = ReplacingMergeTree(ingest_version)
+ORDER BY (slot)
+PARTITION BY toYYYYMM(toDateTime(block_time));
@@ -10,13 +10,15 @@ CREATE TABLE IF NOT EXISTS bundling_analysis
 
     largest_bundle_size UInt32,   -- Size of largest detected bundle (up to 5 transactions with same tip recipient)
     
-    validator_key String,         -- the validator key who built the block
+    validator_key Nullable(String), -- the validator key who built the block (NULL if the leader could not be resolved)
     landing_service String,       -- most common landing service in block (empty if unknown)
     landing_service_count UInt32, -- number of transactions using identified landing service
+    cpi_tip_count UInt32,         -- number of transactions whose tip was paid through a CPI
     
-    created_at DateTime DEFAULT now()
+    created_at DateTime DEFAULT now(),
+    ingest_version UInt64 DEFAULT toUnixTimestamp64Milli(now64(3))
 )
-ENGINE = MergeTree()
+ENGINE = ReplacingMergeTree(ingest_version)
 ORDER BY (slot)
 PARTITION BY toYYYYMM(toDateTime(block_time));
 
@@ -0,0 +1,16 @@
+-- slots that still failed after every retry, kept so the retry-failed command can attempt them again.
+-- A slot that is recovered gets a newer row with resolved = 1, which replaces the failure on merge
+
+USE solana;
+
+CREATE TABLE IF NOT EXISTS failed_slots
+(
+    slot UInt64,
+    error_kind LowCardinality(String),  -- IngestError::kind, e.g. 'rpc', 'slot_pruned', 'rate_limited' or 'sink'
+    error String,
+    attempts UInt32,                    -- attempts made over every run, including retry-failed
+    resolved UInt8,                     -- 1 once the block was stored
+    updated_at DateTime64(3) DEFAULT now64(3)
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY (slot);
@@ -9,9 +9,10 @@ CREATE TABLE IF NOT EXISTS fee_by_transaction_type
     transaction_type String,           -- the type of transaction (vote, system, spl_token, jupiter, raydium, orca, other)
     transaction_count UInt32,
     total_fee UInt64,
-    created_at DateTime DEFAULT now()
+    created_at DateTime DEFAULT now(),
+    ingest_version UInt64 DEFAULT toUnixTimestamp64Milli(now64(3))
 )
-ENGINE = MergeTree()
+ENGINE = ReplacingMergeTree(ingest_version)
 ORDER BY (slot, transaction_type)
 PARTITION BY toYYYYMM(toDateTime(block_time));
 
@@ -7,9 +7,21 @@ CREATE TABLE IF NOT EXISTS fee_landscape
     block_time UInt64,
     fee_avg Float64,
     compute_budget_percent Float32,
-    created_at DateTime DEFAULT now()
+    total_compute_units UInt64,
+    compute_units_p50 UInt64,
+    compute_units_p90 UInt64,
+    compute_units_p99 UInt64,
+    block_cu_utilization_percent Float32,
+    total_base_fee UInt64,
+    total_priority_fee UInt64,
+    total_tip UInt64,
+    compute_unit_price_p50 UInt64,
+    compute_unit_price_p90 UInt64,
+    compute_unit_limit_avg Float64,
+    created_at DateTime DEFAULT now(),
+    ingest_version UInt64 DEFAULT toUnixTimestamp64Milli(now64(3))
 )
-ENGINE = MergeTree()
+ENGINE = ReplacingMergeTree(ingest_version)
 ORDER BY (slot)
 PARTITION BY toYYYYMM(toDateTime(block_time));
 
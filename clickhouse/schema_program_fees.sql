@@ -7,14 +7,21 @@ CREATE TABLE IF NOT EXISTS program_fee_analysis
     slot UInt64,
     block_time UInt64,
     program_type String,
+    program_id String,                 -- the primary program; several programs can share a name
     program_name String,
     transaction_count UInt32,
     total_fee UInt64,
     min_fee UInt64,
     max_fee UInt64,
-    created_at DateTime DEFAULT now()
+    total_base_fee UInt64,
+    total_priority_fee UInt64,
+    total_tip UInt64,
+    compute_unit_price_avg Float64,
+    compute_unit_limit_avg Float64,
+    created_at DateTime DEFAULT now(),
+    ingest_version UInt64 DEFAULT toUnixTimestamp64Milli(now64(3))
 )
-ENGINE = MergeTree()
-ORDER BY (slot, program_type)
+ENGINE = ReplacingMergeTree(ingest_version)
+ORDER BY (slot, program_type, program_id)
 PARTITION BY toYYYYMM(toDateTime(block_time));
 
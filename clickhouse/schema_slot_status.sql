@@ -0,0 +1,16 @@
+-- this records what happened to every slot the ingester looked at, so skipped leader slots can be told
+-- apart from blocks that exist but could not be fetched
+
+USE solana;
+
+CREATE TABLE IF NOT EXISTS slot_status
+(
+    slot UInt64,
+    epoch Nullable(UInt64),
+    status LowCardinality(String),     -- 'produced', 'skipped' (the leader produced no block) or 'unavailable'
+    leader Nullable(String),           -- the scheduled leader, NULL if the schedule could not be read
+    error String,                      -- why the block was skipped or unavailable, empty when produced
+    updated_at DateTime64(3) DEFAULT now64(3)  -- millisecond precision, so a later outcome in the same second still wins
+)
+ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY (slot);
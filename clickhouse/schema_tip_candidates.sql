@@ -0,0 +1,23 @@
+-- this collects accounts outside the landing service registry that keep receiving transfers from many payers,
+-- so likely tip accounts of unknown landing services can be reviewed and added to data/landing_services.toml
+
+USE solana;
+
+CREATE TABLE IF NOT EXISTS tip_account_candidates
+(
+    recipient String,
+    first_seen_slot UInt64,        -- first transfer seen since the recipient was tracked, kept across restarts
+    last_seen_slot UInt64,
+    transfer_count UInt64,
+    distinct_payers UInt32,        -- capped at 1000
+    total_lamports UInt64,
+    min_tip UInt64,
+    p50_tip UInt64,
+    p90_tip UInt64,
+    max_tip UInt64,
+    validator_count UInt32,
+    validators Array(String),      -- leaders of the blocks the transfers landed in
+    updated_at DateTime DEFAULT now()
+)
+ENGINE = ReplacingMergeTree(last_seen_slot)
+ORDER BY recipient;
@@ -0,0 +1,33 @@
+-- this stores every parsed transaction (opt-in with analysis.store_transactions) for questions the
+-- block-level tables cannot answer, such as which fee payers overpaid or where tipped transactions landed
+
+USE solana;
+
+CREATE TABLE IF NOT EXISTS transactions
+(
+    slot UInt64,
+    block_time UInt64,
+    position UInt32,               -- 1-based position of the transaction in the block
+    signature String,
+    fee_payer String,
+    fee UInt64,
+    base_fee UInt64,
+    priority_fee UInt64,
+    compute_unit_price UInt64,     -- micro-lamports per CU (0 if not set)
+    compute_unit_limit UInt32,     -- requested CU limit (0 if not set)
+    compute_units_consumed UInt64,
+    tip_amount UInt64,
+    tip_recipient String,
+    tip_via_cpi UInt8,
+    landing_service LowCardinality(String),
+    primary_program String,
+    program_name LowCardinality(String),
+    transaction_type LowCardinality(String),
+    failed UInt8,
+    created_at DateTime DEFAULT now(),
+    ingest_version UInt64 DEFAULT toUnixTimestamp64Milli(now64(3))
+)
+ENGINE = ReplacingMergeTree(ingest_version)
+ORDER BY (slot, position)
+PARTITION BY toYYYYMM(toDateTime(block_time));
+-- retention (analysis.transactions_retention_days) is applied as a TTL on created_at at startup
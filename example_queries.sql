@@ -1,3 +1,6 @@
+-- Tables are deduplicated by slot in the background, so FINAL is used to read only the latest
+-- row of slots that were processed more than once
+
 -- 1. The Solana Program that Overcharges Users
 SELECT 
     program_name,
@@ -5,7 +8,7 @@ SELECT
     SUM(transaction_count) as total_transactions,
     ROUND(AVG(total_fee / transaction_count), 0) as avg_fee_per_tx_lamports,
     SUM(total_fee) as total_fees_lamports
-FROM solana.program_fee_analysis
+FROM solana.program_fee_analysis FINAL
 GROUP BY program_name, program_type
 HAVING total_transactions > 1000
     AND quantile(0.9)(max_fee) > 100000  -- High p90 fees (potential overcharging)
@@ -32,8 +35,8 @@ SELECT
     ROUND(quantile(0.9)(ftt.total_fee / ftt.transaction_count), 0) as p90_swap_fee_lamports,
     ROUND(quantile(0.99)(ftt.total_fee / ftt.transaction_count), 0) as p99_swap_fee_lamports
     
-FROM solana.bundling_analysis ba
-JOIN solana.fee_by_transaction_type ftt ON ba.slot = ftt.slot
+FROM solana.bundling_analysis ba FINAL
+JOIN solana.fee_by_transaction_type ftt FINAL ON ba.slot = ftt.slot
 WHERE ftt.transaction_type IN ('jupiter', 'raydium', 'orca')  -- DEX swaps
 GROUP BY landing_service, ftt.transaction_type
 ORDER BY swap_fees_lamports DESC;
@@ -45,14 +48,13 @@ WITH landing_service_stats AS (
     SELECT 
         CASE 
             WHEN landing_service = 'Jito' THEN 'Jito'
-            WHEN landing_service LIKE 'Unknown:%' THEN landing_service
             WHEN landing_service = 'No Tip' THEN 'No Tip'
             ELSE landing_service
         END as landing_service,
         COUNT(DISTINCT slot) as blocks_landed,
         SUM(landing_service_count) as total_transactions,
         ROUND(AVG(largest_bundle_size), 2) as avg_bundle_size
-    FROM solana.bundling_analysis
+    FROM solana.bundling_analysis FINAL
     WHERE landing_service != 'No Tip'  -- Exclude non-tipped transactions
     GROUP BY landing_service
 ),
@@ -88,13 +90,13 @@ WITH pump_fun_stats AS (
         total_fee,
         transaction_count,
         total_fee / NULLIF(transaction_count, 0) as avg_fee_per_tx
-    FROM solana.program_fee_analysis
+    FROM solana.program_fee_analysis FINAL
     WHERE program_name = '6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P' -- FLASHX8DrLbgeR8FcfNV1F5krxYcYMUdBkrP1EPBtxB9
 ),
 overall_avg AS (
     SELECT 
         AVG(total_fee / NULLIF(transaction_count, 0)) as overall_avg_fee_per_tx
-    FROM solana.program_fee_analysis
+    FROM solana.program_fee_analysis FINAL
     WHERE program_name != '6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P' -- FLASHX8DrLbgeR8FcfNV1F5krxYcYMUdBkrP1EPBtxB9
 )
 SELECT 
@@ -135,6 +137,20 @@ SELECT
     ROUND(SUM(CASE WHEN landing_service = 'Jito' AND largest_bundle_size >= 2 THEN landing_service_count ELSE 0 END) * 100.0 / 
           NULLIF(SUM(CASE WHEN landing_service = 'Jito' THEN landing_service_count ELSE 0 END), 0), 2) as pct_jito_txs_in_bundles
 
-FROM solana.bundling_analysis;
+FROM solana.bundling_analysis FINAL;
 
--- ============================================================================
\ No newline at end of file
+-- ============================================================================
+-- 6. Validator Skip Rates
+-- ============================================================================
+-- Validators that skipped the largest share of their leader slots in each epoch
+SELECT
+    epoch,
+    leader,
+    produced,
+    skipped,
+    unavailable,
+    ROUND(skip_rate * 100, 2) as skip_rate_pct
+FROM solana.validator_skip_rates
+WHERE produced + skipped >= 100
+ORDER BY epoch DESC, skip_rate DESC
+LIMIT 20;
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use super::types::Transaction;
 use super::landing_services::identify_landing_service;
+use super::registry::{Analyzer, BlockContext, RowBatch};
 
 #[derive(Debug, Row, Serialize, Deserialize)]
 pub struct BundlingAnalysisRow {
@@ -10,9 +11,10 @@ pub struct BundlingAnalysisRow {
     pub blockhash: String,
     pub block_time: u64,
     pub largest_bundle_size: u32,  // Size of largest detected bundle (up to 5 transactions)
-    pub validator_key: String,
+    pub validator_key: Option<String>,  // None if the leader could not be resolved
     pub landing_service: String,  // Most common landing service in block (empty if unknown)
     pub landing_service_count: u32,  // Number of transactions using identified landing service
+    pub cpi_tip_count: u32,  // Number of transactions whose tip was paid through a CPI
 }
 
 pub fn analyze_bundling(
@@ -20,7 +22,7 @@ pub fn analyze_bundling(
     slot: u64,
     blockhash: String,
     block_time: u64,
-    validator_key: String,
+    validator_key: Option<String>,
 ) -> BundlingAnalysisRow {
     // Detect Jito bundles: sequential transactions (up to 5) with same tip recipient
     // According to Jito docs: bundles are groups of transactions (max 5) executed sequentially and atomically
@@ -90,17 +92,15 @@ pub fn analyze_bundling(
     let mut landing_service_count = 0u32;
     
     if let Some((recipient, count)) = tip_recipient_counts.iter().max_by_key(|(_, &c)| c) {
-        // Try to identify the service
+        // Tip recipients are always known landing service accounts
         if let Some(service) = identify_landing_service(recipient) {
             landing_service_found = service;
             landing_service_count = *count;
-        } else {
-            // Unknown landing service - use recipient address as identifier
-            landing_service_found = format!("Unknown: {}", recipient);
-            landing_service_count = *count;
         }
     }
 
+    let cpi_tip_count = transactions.iter().filter(|tx| tx.tip_amount > 0 && tx.tip_via_cpi == 1).count() as u32;
+
     BundlingAnalysisRow {
         slot,
         blockhash,
@@ -109,6 +109,32 @@ pub fn analyze_bundling(
         validator_key,
         landing_service: landing_service_found,
         landing_service_count,
+        cpi_tip_count,
     }
 }
 
+pub struct BundlingAnalyzer;
+
+impl Analyzer for BundlingAnalyzer {
+    fn name(&self) -> &'static str {
+        "bundling"
+    }
+
+    fn table(&self) -> &'static str {
+        "bundling_analysis"
+    }
+
+    fn schema(&self) -> &'static str {
+        include_str!("../../clickhouse/schema_bundling.sql")
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch> {
+        Box::new(vec![analyze_bundling(
+            block.transactions,
+            block.slot,
+            block.blockhash.to_string(),
+            block.block_time,
+            block.validator_key.map(str::to_string),
+        )])
+    }
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+
+/// Storage-neutral type of a row column
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnType {
+    Bool,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    String,
+    List(Box<ColumnType>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+    pub nullable: bool,  // The field is an Option
+}
+
+/// The columns of row type `T`, in field order. They are read from the type's `Deserialize`
+/// implementation rather than from row values, so every batch of a table gets the same columns.
+pub fn columns_of<T: DeserializeOwned>() -> Result<Vec<Column>, String> {
+    let mut columns = Vec::new();
+    T::deserialize(RowTracer { columns: &mut columns }).map_err(|e| e.0)?;
+    Ok(columns)
+}
+
+#[derive(Debug)]
+struct TraceError(String);
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl de::Error for TraceError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        TraceError(msg.to_string())
+    }
+}
+
+/// Feeds a placeholder value to every field of a struct, recording the type each field asks for
+struct RowTracer<'a> {
+    columns: &'a mut Vec<Column>,
+}
+
+impl<'de> de::Deserializer<'de> for RowTracer<'_> {
+    type Error = TraceError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, TraceError> {
+        Err(TraceError("rows must be structs with named fields".to_string()))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, TraceError> {
+        visitor.visit_map(FieldAccess { fields, next: 0, columns: self.columns })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct FieldAccess<'a> {
+    fields: &'static [&'static str],
+    next: usize,
+    columns: &'a mut Vec<Column>,
+}
+
+impl<'de> MapAccess<'de> for FieldAccess<'_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, TraceError> {
+        match self.fields.get(self.next) {
+            Some(field) => seed.deserialize(IntoDeserializer::<TraceError>::into_deserializer(*field)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, TraceError> {
+        let name = self.fields[self.next];
+        self.next += 1;
+        let mut column_type = None;
+        let mut nullable = false;
+        let value = seed.deserialize(ValueTracer { column_type: &mut column_type, nullable: &mut nullable })?;
+        let column_type = column_type.ok_or_else(|| TraceError(format!("column {} has no type", name)))?;
+        self.columns.push(Column { name, column_type, nullable });
+        Ok(value)
+    }
+}
+
+struct ValueTracer<'a> {
+    column_type: &'a mut Option<ColumnType>,
+    nullable: &'a mut bool,
+}
+
+macro_rules! trace_primitives {
+    ($($method:ident => $column_type:ident, $visit:ident($value:expr);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+                *self.column_type = Some(ColumnType::$column_type);
+                visitor.$visit($value)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueTracer<'_> {
+    type Error = TraceError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, TraceError> {
+        Err(TraceError("unsupported column type".to_string()))
+    }
+
+    trace_primitives! {
+        deserialize_bool => Bool, visit_bool(false);
+        deserialize_u8 => UInt8, visit_u8(0);
+        deserialize_u16 => UInt16, visit_u16(0);
+        deserialize_u32 => UInt32, visit_u32(0);
+        deserialize_u64 => UInt64, visit_u64(0);
+        deserialize_i8 => Int8, visit_i8(0);
+        deserialize_i16 => Int16, visit_i16(0);
+        deserialize_i32 => Int32, visit_i32(0);
+        deserialize_i64 => Int64, visit_i64(0);
+        deserialize_f32 => Float32, visit_f32(0.0);
+        deserialize_f64 => Float64, visit_f64(0.0);
+        deserialize_char => String, visit_char(' ');
+        deserialize_str => String, visit_str("");
+        deserialize_string => String, visit_string(String::new());
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        *self.nullable = true;
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, TraceError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, TraceError> {
+        let mut item = None;
+        let value = visitor.visit_seq(ElementAccess { item: &mut item, done: false })?;
+        let item = item.ok_or_else(|| TraceError("list items have no type".to_string()))?;
+        *self.column_type = Some(ColumnType::List(Box::new(item)));
+        Ok(value)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// A list with a single placeholder item, to record the item type
+struct ElementAccess<'a> {
+    item: &'a mut Option<ColumnType>,
+    done: bool,
+}
+
+impl<'de> SeqAccess<'de> for ElementAccess<'_> {
+    type Error = TraceError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, TraceError> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        let mut nullable = false;
+        seed.deserialize(ValueTracer { column_type: &mut *self.item, nullable: &mut nullable }).map(Some)
+    }
+}
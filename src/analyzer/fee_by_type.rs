@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use super::types::Transaction;
 use super::utils::get_transaction_type;
+use super::registry::{Analyzer, BlockContext, RowBatch};
 
 #[derive(Debug, Row, Serialize, Deserialize)]
 pub struct FeeByTransactionTypeRow {
@@ -22,7 +23,7 @@ pub fn analyze_fee_by_type(
 
     for tx in transactions {
         let tx_type = get_transaction_type(tx);
-        type_fees.entry(tx_type).or_insert_with(Vec::new).push((tx.fee, tx.failed, tx.has_compute_budget));
+        type_fees.entry(tx_type).or_default().push((tx.fee, tx.failed, tx.has_compute_budget));
     }
 
     let mut fee_by_type_rows = Vec::new();
@@ -43,3 +44,22 @@ pub fn analyze_fee_by_type(
     fee_by_type_rows
 }
 
+pub struct FeeByTypeAnalyzer;
+
+impl Analyzer for FeeByTypeAnalyzer {
+    fn name(&self) -> &'static str {
+        "fee_by_type"
+    }
+
+    fn table(&self) -> &'static str {
+        "fee_by_transaction_type"
+    }
+
+    fn schema(&self) -> &'static str {
+        include_str!("../../clickhouse/schema_fee_by_type.sql")
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch> {
+        Box::new(analyze_fee_by_type(block.transactions, block.slot, block.block_time))
+    }
+}
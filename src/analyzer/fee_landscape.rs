@@ -1,6 +1,11 @@
 use clickhouse::Row;
 use serde::{Deserialize, Serialize};
 use super::types::Transaction;
+use super::utils::{calculate_percentile, mean_of_nonzero};
+use super::registry::{Analyzer, BlockContext, RowBatch};
+
+// Maximum compute units the cluster packs into a single block
+pub const MAX_BLOCK_COMPUTE_UNITS: u64 = 48_000_000;
 
 #[derive(Debug, Row, Serialize, Deserialize)]
 pub struct FeeLandscapeRow {
@@ -8,6 +13,17 @@ pub struct FeeLandscapeRow {
     pub block_time: u64,
     pub fee_avg: f64,
     pub compute_budget_percent: f32,
+    pub total_compute_units: u64,
+    pub compute_units_p50: u64,
+    pub compute_units_p90: u64,
+    pub compute_units_p99: u64,
+    pub block_cu_utilization_percent: f32,  // total_compute_units against MAX_BLOCK_COMPUTE_UNITS
+    pub total_base_fee: u64,
+    pub total_priority_fee: u64,
+    pub total_tip: u64,
+    pub compute_unit_price_p50: u64,  // Micro-lamports per CU, over transactions that set a price
+    pub compute_unit_price_p90: u64,
+    pub compute_unit_limit_avg: f64,  // Over transactions that set a limit
 }
 
 pub fn analyze_fee_landscape(
@@ -23,11 +39,52 @@ pub fn analyze_fee_landscape(
     let compute_budget_count = compute_budget_txs.len() as u32;
     let compute_budget_percent = (compute_budget_count as f32 / transactions.len() as f32) * 100.0;
 
+    // Compute unit analysis
+    let compute_units: Vec<u64> = transactions.iter().map(|t| t.compute_units_consumed).collect();
+    let total_compute_units = compute_units.iter().sum::<u64>();
+    let block_cu_utilization_percent = (total_compute_units as f64 / MAX_BLOCK_COMPUTE_UNITS as f64 * 100.0) as f32;
+
+    // Fee breakdown
+    let total_base_fee = transactions.iter().map(|t| t.base_fee).sum::<u64>();
+    let total_priority_fee = transactions.iter().map(|t| t.priority_fee).sum::<u64>();
+    let total_tip = transactions.iter().map(|t| t.tip_amount).sum::<u64>();
+    let compute_unit_prices: Vec<u64> = transactions.iter().map(|t| t.compute_unit_price).filter(|&p| p > 0).collect();
+
     FeeLandscapeRow {
         slot,
         block_time,
         fee_avg,
         compute_budget_percent,
+        total_compute_units,
+        compute_units_p50: calculate_percentile(compute_units.clone(), 50.0),
+        compute_units_p90: calculate_percentile(compute_units.clone(), 90.0),
+        compute_units_p99: calculate_percentile(compute_units, 99.0),
+        block_cu_utilization_percent,
+        total_base_fee,
+        total_priority_fee,
+        total_tip,
+        compute_unit_price_p50: calculate_percentile(compute_unit_prices.clone(), 50.0),
+        compute_unit_price_p90: calculate_percentile(compute_unit_prices, 90.0),
+        compute_unit_limit_avg: mean_of_nonzero(transactions.iter().map(|t| t.compute_unit_limit as u64)),
     }
 }
 
+pub struct FeeLandscapeAnalyzer;
+
+impl Analyzer for FeeLandscapeAnalyzer {
+    fn name(&self) -> &'static str {
+        "fee_landscape"
+    }
+
+    fn table(&self) -> &'static str {
+        "fee_landscape"
+    }
+
+    fn schema(&self) -> &'static str {
+        include_str!("../../clickhouse/schema_fees.sql")
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch> {
+        Box::new(vec![analyze_fee_landscape(block.transactions, block.slot, block.block_time)])
+    }
+}
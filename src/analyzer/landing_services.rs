@@ -1,32 +1,107 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
 
-pub fn get_landing_service_addresses() -> HashMap<String, String> {
-    let mut addresses = HashMap::new();
-    
-    // Jito landing service and tip accounts
-    // These are all Jito tip accounts that route through the same landing service
-    let jito_addresses = vec![
-        "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt", // Jito landing service
-        "87wyLh2iDzszjYTPi5tnDhRx5GGrxzWsRAUbBboVm743", // Jito tip account
-        "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49", // Jito tip account
-        "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY", // Jito tip account
-        "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe", // Jito tip account
-        "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh", // Jito tip account
-        "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5", // Jito tip account
-        "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT", // Jito tip account
-        "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL", // Jito tip account
-    ];
-    
-    for addr in jito_addresses {
-        addresses.insert(addr.to_string(), "Jito".to_string());
+/// Registry data compiled into the binary, used unless another file is configured
+pub const BUNDLED_LANDING_SERVICES: &str = include_str!("../../data/landing_services.toml");
+// Registry file format understood by this build
+const REGISTRY_FORMAT_VERSION: u32 = 1;
+
+static REGISTRY: OnceLock<LandingServiceRegistry> = OnceLock::new();
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegistryFile {
+    version: u32,
+    services: Vec<ServiceEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ServiceEntry {
+    name: String,
+    accounts: Vec<String>,
+}
+
+/// Tip account -> landing service name lookup
+#[derive(Debug, Default)]
+pub struct LandingServiceRegistry {
+    version: u32,
+    accounts: HashMap<String, String>,
+}
+
+impl LandingServiceRegistry {
+    pub fn from_toml_str(contents: &str) -> Result<Self, String> {
+        let file: RegistryFile = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if file.version != REGISTRY_FORMAT_VERSION {
+            return Err(format!("unsupported registry version {}, expected {}", file.version, REGISTRY_FORMAT_VERSION));
+        }
+
+        let mut accounts = HashMap::new();
+        for service in file.services {
+            for account in service.accounts {
+                if Pubkey::from_str(&account).is_err() {
+                    return Err(format!("{} account {} is not a valid address", service.name, account));
+                }
+                if let Some(existing) = accounts.insert(account.clone(), service.name.clone()) {
+                    return Err(format!("account {} is listed for both {} and {}", account, existing, service.name));
+                }
+            }
+        }
+        Ok(LandingServiceRegistry { version: file.version, accounts })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read landing service registry {}: {}", path.display(), e))?;
+        Self::from_toml_str(&contents).map_err(|e| format!("Invalid landing service registry {}: {}", path.display(), e))
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn account_count(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Service names with their number of tip accounts, sorted by name
+    pub fn services(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for service in self.accounts.values() {
+            *counts.entry(service.as_str()).or_default() += 1;
+        }
+        let mut services: Vec<(String, usize)> = counts.into_iter().map(|(name, count)| (name.to_string(), count)).collect();
+        services.sort();
+        services
+    }
+
+    pub fn identify(&self, recipient_address: &str) -> Option<&str> {
+        self.accounts.get(recipient_address).map(String::as_str)
     }
-    
-    addresses
+}
+
+/// Load the registry once at startup, from `path` if given or from the bundled data otherwise
+pub fn init_registry(path: Option<&Path>) -> Result<&'static LandingServiceRegistry, String> {
+    let loaded = match path {
+        Some(path) => LandingServiceRegistry::load(path)?,
+        None => LandingServiceRegistry::from_toml_str(BUNDLED_LANDING_SERVICES)?,
+    };
+    REGISTRY.set(loaded).map_err(|_| "landing service registry is already initialized".to_string())?;
+    Ok(registry())
+}
+
+/// The loaded registry, falling back to the bundled data if `init_registry` was not called
+pub fn registry() -> &'static LandingServiceRegistry {
+    REGISTRY.get_or_init(|| {
+        LandingServiceRegistry::from_toml_str(BUNDLED_LANDING_SERVICES).expect("bundled landing service registry is valid")
+    })
 }
 
 /// Identify landing service from transfer recipient address
 pub fn identify_landing_service(recipient_address: &str) -> Option<String> {
-    let service_map = get_landing_service_addresses();
-    service_map.get(recipient_address).cloned()
+    registry().identify(recipient_address).map(str::to_string)
 }
-
@@ -1,8 +1,12 @@
 pub mod bundling;
+pub mod columns;
 pub mod fee_by_type;
 pub mod fee_landscape;
 pub mod landing_services;
 pub mod program_fee;
+pub mod registry;
+pub mod tip_candidates;
+pub mod transactions;
 pub mod types;
 pub mod utils;
 
@@ -2,18 +2,25 @@ use clickhouse::Row;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use super::types::Transaction;
-use super::utils::{categorize_program_type, get_primary_program, get_program_name};
+use super::utils::{categorize_program_type, get_primary_program, get_program_name, mean_of_nonzero};
+use super::registry::{Analyzer, BlockContext, RowBatch};
 
 #[derive(Debug, Row, Serialize, Deserialize)]
 pub struct ProgramFeeAnalysisRow {
     pub slot: u64,
     pub block_time: u64,
     pub program_type: String,
+    pub program_id: String,
     pub program_name: String,
     pub transaction_count: u32,
     pub total_fee: u64,
     pub min_fee: u64,
     pub max_fee: u64,
+    pub total_base_fee: u64,
+    pub total_priority_fee: u64,
+    pub total_tip: u64,
+    pub compute_unit_price_avg: f64,  // Micro-lamports per CU, over transactions that set a price
+    pub compute_unit_limit_avg: f64,  // Over transactions that set a limit
 }
 
 pub fn analyze_program_fees(
@@ -22,19 +29,20 @@ pub fn analyze_program_fees(
     block_time: u64,
 ) -> Vec<ProgramFeeAnalysisRow> {
     // Track fees by program type
-    let mut program_fees: HashMap<(String, String), Vec<u64>> = HashMap::new(); // (program_type, program_id) -> fees
+    let mut program_fees: HashMap<(String, String), Vec<&Transaction>> = HashMap::new(); // (program_type, program_id) -> transactions
 
     for tx in transactions {
         if let Some(primary_program) = get_primary_program(&tx.program_ids) {
             let (prog_type, _) = categorize_program_type(&primary_program);
             let key = (prog_type.to_string(), primary_program);
-            program_fees.entry(key).or_insert_with(Vec::new).push(tx.fee);
+            program_fees.entry(key).or_default().push(tx);
         }
     }
 
     // Create program fee analysis rows
     let mut program_fee_rows = Vec::new();
-    for ((prog_type, prog_id), fees) in program_fees {
+    for ((prog_type, prog_id), txs) in program_fees {
+        let fees: Vec<u64> = txs.iter().map(|t| t.fee).collect();
         let count = fees.len() as u32;
         let total_fee = fees.iter().sum::<u64>();
         let min_fee = *fees.iter().min().unwrap_or(&0);
@@ -45,17 +53,42 @@ pub fn analyze_program_fees(
             slot,
             block_time,
             program_type: prog_type.clone(),
+            program_id: prog_id,
             program_name,
             transaction_count: count,
             total_fee,
             min_fee,
             max_fee,
+            total_base_fee: txs.iter().map(|t| t.base_fee).sum(),
+            total_priority_fee: txs.iter().map(|t| t.priority_fee).sum(),
+            total_tip: txs.iter().map(|t| t.tip_amount).sum(),
+            compute_unit_price_avg: mean_of_nonzero(txs.iter().map(|t| t.compute_unit_price)),
+            compute_unit_limit_avg: mean_of_nonzero(txs.iter().map(|t| t.compute_unit_limit as u64)),
         });
     }
 
     // Sort by total_fee descending to see what's paying the most
-    program_fee_rows.sort_by(|a, b| b.total_fee.cmp(&a.total_fee));
+    program_fee_rows.sort_by_key(|row| std::cmp::Reverse(row.total_fee));
 
     program_fee_rows
 }
 
+pub struct ProgramFeeAnalyzer;
+
+impl Analyzer for ProgramFeeAnalyzer {
+    fn name(&self) -> &'static str {
+        "program_fee"
+    }
+
+    fn table(&self) -> &'static str {
+        "program_fee_analysis"
+    }
+
+    fn schema(&self) -> &'static str {
+        include_str!("../../clickhouse/schema_program_fees.sql")
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch> {
+        Box::new(analyze_program_fees(block.transactions, block.slot, block.block_time))
+    }
+}
@@ -0,0 +1,147 @@
+use std::any::Any;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use super::bundling::BundlingAnalyzer;
+use super::columns::{columns_of, Column};
+use super::fee_by_type::FeeByTypeAnalyzer;
+use super::fee_landscape::FeeLandscapeAnalyzer;
+use super::program_fee::ProgramFeeAnalyzer;
+use super::tip_candidates::TipCandidateAnalyzer;
+use super::transactions::TransactionsAnalyzer;
+use super::types::Transaction;
+
+/// Everything an analyzer gets to see about a single block
+#[derive(Debug, Clone, Copy)]
+pub struct BlockContext<'a> {
+    pub slot: u64,
+    pub blockhash: &'a str,
+    pub block_time: u64,
+    pub transactions: &'a [Transaction],
+    pub validator_key: Option<&'a str>,  // None if the leader could not be resolved
+}
+
+/// Rows produced by an analyzer for one block, with their concrete row type erased.
+/// Sinks decide how the serialized rows are stored.
+pub trait RowBatch: Send + Sync {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Columns of the row type, the same for every batch of a table, even an empty one
+    fn columns(&self) -> Result<Vec<Column>, String>;
+
+    /// Every row as a JSON object with one key per column, in column order
+    fn to_json(&self) -> Result<Vec<serde_json::Value>, serde_json::Error>;
+
+    /// The batch itself, so sinks that store typed rows can recover the row type
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Serialize + DeserializeOwned + Send + Sync + 'static> RowBatch for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn columns(&self) -> Result<Vec<Column>, String> {
+        columns_of::<T>()
+    }
+
+    fn to_json(&self) -> Result<Vec<serde_json::Value>, serde_json::Error> {
+        self.iter().map(serde_json::to_value).collect()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A self-contained signal computed per block and stored in its own table
+pub trait Analyzer: Send + Sync {
+    /// Name used to enable or disable the analyzer in the config
+    fn name(&self) -> &'static str;
+
+    fn table(&self) -> &'static str;
+
+    /// `CREATE TABLE IF NOT EXISTS` statement for `table`
+    fn schema(&self) -> &'static str;
+
+    /// Analyzers that are expensive to store can be left off unless explicitly enabled
+    fn enabled_by_default(&self) -> bool {
+        true
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch>;
+
+    /// Whether the analyzer keeps state across blocks that is rebuilt from its stored rows on startup
+    fn is_stateful(&self) -> bool {
+        false
+    }
+
+    /// Rebuild the state kept across blocks from the rows an earlier run stored in `table`
+    fn restore(&self, _rows: &dyn RowBatch) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Every built-in analyzer, in the order its rows are written
+pub fn builtin_analyzers() -> Vec<Box<dyn Analyzer>> {
+    vec![
+        Box::new(BundlingAnalyzer),
+        Box::new(FeeLandscapeAnalyzer),
+        Box::new(ProgramFeeAnalyzer),
+        Box::new(FeeByTypeAnalyzer),
+        Box::new(TipCandidateAnalyzer::new()),
+        Box::new(TransactionsAnalyzer),
+    ]
+}
+
+/// The registered analyzers and which of them run on each block
+pub struct AnalyzerRegistry {
+    analyzers: Vec<(Box<dyn Analyzer>, bool)>,
+}
+
+impl AnalyzerRegistry {
+    /// Register `analyzers`, each with its default enabled state
+    pub fn new(analyzers: Vec<Box<dyn Analyzer>>) -> Self {
+        AnalyzerRegistry {
+            analyzers: analyzers
+                .into_iter()
+                .map(|analyzer| {
+                    let enabled = analyzer.enabled_by_default();
+                    (analyzer, enabled)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn builtin() -> Self {
+        Self::new(builtin_analyzers())
+    }
+
+    /// Enable or disable an analyzer by name
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        match self.analyzers.iter_mut().find(|(analyzer, _)| analyzer.name() == name) {
+            Some((_, state)) => {
+                *state = enabled;
+                Ok(())
+            }
+            None => Err(format!("unknown analyzer '{}', expected one of: {}", name, self.names().join(", "))),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.analyzers.iter().map(|(analyzer, _)| analyzer.name()).collect()
+    }
+
+    /// Every registered analyzer, enabled or not. Tables are created and cleared for all of them.
+    pub fn all(&self) -> impl Iterator<Item = &dyn Analyzer> {
+        self.analyzers.iter().map(|(analyzer, _)| analyzer.as_ref())
+    }
+
+    /// The analyzers that run on each block
+    pub fn enabled(&self) -> impl Iterator<Item = &dyn Analyzer> {
+        self.analyzers.iter().filter(|(_, enabled)| *enabled).map(|(analyzer, _)| analyzer.as_ref())
+    }
+}
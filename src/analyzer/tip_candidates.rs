@@ -0,0 +1,296 @@
+use clickhouse::Row;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use super::types::Transaction;
+use super::utils::calculate_percentile;
+use super::registry::{Analyzer, BlockContext, RowBatch};
+
+// A recipient becomes a candidate once it has been tipped this often by this many different payers
+pub const MIN_CANDIDATE_TRANSFERS: u64 = 10;
+pub const MIN_CANDIDATE_PAYERS: usize = 5;
+// Recipients that are not candidates are forgotten after this many slots without a transfer (~1 hour)
+pub const CANDIDATE_WINDOW_SLOTS: u64 = 9000;
+// Candidates are forgotten after this many slots without a transfer (~1 day); their last row stays stored
+pub const CANDIDATE_RETENTION_SLOTS: u64 = 216_000;
+// Tip amounts sampled per recipient for the size distribution
+pub const MAX_AMOUNT_SAMPLES: usize = 10_000;
+// Distinct payers remembered per recipient; distinct_payers stops counting here
+pub const MAX_TRACKED_PAYERS: usize = 1000;
+
+#[derive(Debug, Row, Serialize, Deserialize)]
+pub struct TipAccountCandidateRow {
+    pub recipient: String,
+    pub first_seen_slot: u64,
+    pub last_seen_slot: u64,
+    pub transfer_count: u64,
+    pub distinct_payers: u32,     // Capped at MAX_TRACKED_PAYERS
+    pub total_lamports: u64,
+    pub min_tip: u64,
+    pub p50_tip: u64,
+    pub p90_tip: u64,
+    pub max_tip: u64,
+    pub validator_count: u32,
+    pub validators: Vec<String>,  // Leaders of the blocks the transfers landed in
+}
+
+#[derive(Debug, Default)]
+struct RecipientStats {
+    first_seen_slot: u64,
+    last_seen_slot: u64,
+    transfer_count: u64,
+    total_lamports: u64,
+    min_tip: u64,
+    max_tip: u64,
+    payers: HashSet<String>,
+    restored_payers: usize,  // Distinct payers stored by an earlier run, known only by count
+    amounts: Vec<u64>,       // Uniform sample of every amount seen
+    validators: HashSet<String>,
+}
+
+impl RecipientStats {
+    /// Stats as stored by an earlier run. The amount sample is rebuilt from the stored quantiles.
+    fn from_row(row: &TipAccountCandidateRow) -> Self {
+        RecipientStats {
+            first_seen_slot: row.first_seen_slot,
+            last_seen_slot: row.last_seen_slot,
+            transfer_count: row.transfer_count,
+            total_lamports: row.total_lamports,
+            min_tip: row.min_tip,
+            max_tip: row.max_tip,
+            payers: HashSet::new(),
+            restored_payers: row.distinct_payers as usize,
+            amounts: sample_from_quantiles(row),
+            validators: row.validators.iter().cloned().collect(),
+        }
+    }
+
+    /// Payers seen before a restart may be seen again, so this is a lower bound once restored
+    fn distinct_payers(&self) -> usize {
+        self.payers.len().max(self.restored_payers)
+    }
+
+    fn is_candidate(&self) -> bool {
+        self.transfer_count >= MIN_CANDIDATE_TRANSFERS && self.distinct_payers() >= MIN_CANDIDATE_PAYERS
+    }
+
+    fn record_transfer(&mut self, slot: u64, payer: &str, amount: u64) {
+        self.last_seen_slot = slot;
+        self.transfer_count += 1;
+        self.total_lamports += amount;
+        self.min_tip = if self.transfer_count == 1 { amount } else { self.min_tip.min(amount) };
+        self.max_tip = self.max_tip.max(amount);
+        if self.payers.len() < MAX_TRACKED_PAYERS {
+            self.payers.insert(payer.to_string());
+        }
+        // Reservoir sampling: every amount seen ends up in the sample with the same probability
+        if self.amounts.len() < MAX_AMOUNT_SAMPLES {
+            self.amounts.push(amount);
+        } else {
+            let index = rand::thread_rng().gen_range(0..self.transfer_count) as usize;
+            if index < MAX_AMOUNT_SAMPLES {
+                self.amounts[index] = amount;
+            }
+        }
+    }
+
+    /// Slots without a transfer after which the recipient is forgotten
+    fn window(&self) -> u64 {
+        if self.is_candidate() {
+            CANDIDATE_RETENTION_SLOTS
+        } else {
+            CANDIDATE_WINDOW_SLOTS
+        }
+    }
+
+    fn to_row(&self, recipient: &str) -> TipAccountCandidateRow {
+        let mut validators: Vec<String> = self.validators.iter().cloned().collect();
+        validators.sort();
+        TipAccountCandidateRow {
+            recipient: recipient.to_string(),
+            first_seen_slot: self.first_seen_slot,
+            last_seen_slot: self.last_seen_slot,
+            transfer_count: self.transfer_count,
+            distinct_payers: self.distinct_payers() as u32,
+            total_lamports: self.total_lamports,
+            min_tip: self.min_tip,
+            p50_tip: calculate_percentile(self.amounts.clone(), 50.0),
+            p90_tip: calculate_percentile(self.amounts.clone(), 90.0),
+            max_tip: self.max_tip,
+            validator_count: validators.len() as u32,
+            validators,
+        }
+    }
+}
+
+/// Amounts spread along the stored tip distribution of `row`, interpolated between its minimum,
+/// median, 90th percentile and maximum, so new amounts are sampled against the full history
+fn sample_from_quantiles(row: &TipAccountCandidateRow) -> Vec<u64> {
+    let len = row.transfer_count.min(MAX_AMOUNT_SAMPLES as u64) as usize;
+    let points = [(0.0, row.min_tip), (0.5, row.p50_tip), (0.9, row.p90_tip), (1.0, row.max_tip)];
+    (0..len)
+        .map(|i| {
+            let quantile = i as f64 / len as f64;
+            let segment = points.windows(2).find(|segment| quantile < segment[1].0).unwrap_or(&points[2..]);
+            let ((q0, v0), (q1, v1)) = (segment[0], segment[1]);
+            (v0 as f64 + (v1 as f64 - v0 as f64) * (quantile - q0) / (q1 - q0)).round() as u64
+        })
+        .collect()
+}
+
+/// Tracks transfers to accounts that are not in the landing service registry across blocks.
+/// An account that keeps receiving transfers from many different payers is likely the tip
+/// account of a landing service that is not known yet.
+///
+/// The state is bounded: payers and amounts are capped per recipient, and recipients are
+/// forgotten once they have been quiet for their window.
+#[derive(Debug, Default)]
+pub struct TipCandidateTracker {
+    recipients: HashMap<String, RecipientStats>,
+    observed_slots: BTreeSet<u64>,  // Slots already counted, so analyzing a slot again adds nothing
+    oldest_slot: u64,               // Slots before this were pruned and are no longer counted
+}
+
+impl TipCandidateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count the unknown tip transfers of the block at `slot`. Returns false, counting nothing, if
+    /// the slot was counted before or is older than the pruned window.
+    pub fn observe_block(&mut self, transactions: &[Transaction], slot: u64, validator_key: Option<&str>) -> bool {
+        if slot < self.oldest_slot || !self.observed_slots.insert(slot) {
+            return false;
+        }
+        for tx in transactions {
+            // Failed transactions move no lamports
+            if tx.failed == 1 {
+                continue;
+            }
+            for transfer in tx.tip_transfers.iter().filter(|t| t.landing_service.is_none()) {
+                // Transfers back to the payer are not tips
+                if transfer.recipient == tx.fee_payer {
+                    continue;
+                }
+                let stats = self.recipients.entry(transfer.recipient.clone()).or_insert_with(|| RecipientStats {
+                    first_seen_slot: slot,
+                    ..Default::default()
+                });
+                stats.record_transfer(slot, &tx.fee_payer, transfer.amount);
+                if let Some(validator) = validator_key {
+                    stats.validators.insert(validator.to_string());
+                }
+            }
+        }
+        true
+    }
+
+    /// Continue from the candidates an earlier run stored. Slots up to the newest stored transfer
+    /// may already be part of the stored counts, so they are not counted again.
+    pub fn restore(&mut self, rows: &[TipAccountCandidateRow]) {
+        for row in rows {
+            self.recipients.insert(row.recipient.clone(), RecipientStats::from_row(row));
+        }
+        if let Some(last_seen_slot) = rows.iter().map(|row| row.last_seen_slot).max() {
+            self.oldest_slot = self.oldest_slot.max(last_seen_slot + 1);
+            self.observed_slots = self.observed_slots.split_off(&self.oldest_slot);
+        }
+    }
+
+    /// Forget recipients that have been quiet for their window, and the slots counted before
+    /// the longest window
+    pub fn prune(&mut self, slot: u64) {
+        self.recipients.retain(|_, stats| stats.last_seen_slot + stats.window() >= slot);
+        self.oldest_slot = self.oldest_slot.max(slot.saturating_sub(CANDIDATE_RETENTION_SLOTS));
+        self.observed_slots = self.observed_slots.split_off(&self.oldest_slot);
+    }
+
+    /// Recipients that have crossed the candidate thresholds, most transfers first
+    pub fn candidates(&self) -> Vec<TipAccountCandidateRow> {
+        let mut rows: Vec<TipAccountCandidateRow> = self
+            .recipients
+            .iter()
+            .filter(|(_, stats)| stats.is_candidate())
+            .map(|(recipient, stats)| stats.to_row(recipient))
+            .collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.transfer_count));
+        rows
+    }
+
+    pub fn tracked_recipients(&self) -> usize {
+        self.recipients.len()
+    }
+}
+
+// Tip account candidates are written out every this many slots
+pub const TIP_CANDIDATE_FLUSH_INTERVAL: u64 = 100;
+
+/// Unknown tip recipients are tracked across blocks, so the tracker outlives a single block
+pub struct TipCandidateAnalyzer {
+    tracker: Mutex<TipCandidateTracker>,
+    last_flush_slot: AtomicU64,
+}
+
+impl TipCandidateAnalyzer {
+    pub fn new() -> Self {
+        TipCandidateAnalyzer {
+            tracker: Mutex::new(TipCandidateTracker::new()),
+            last_flush_slot: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Default for TipCandidateAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Analyzer for TipCandidateAnalyzer {
+    fn name(&self) -> &'static str {
+        "tip_candidates"
+    }
+
+    fn table(&self) -> &'static str {
+        "tip_account_candidates"
+    }
+
+    fn schema(&self) -> &'static str {
+        include_str!("../../clickhouse/schema_tip_candidates.sql")
+    }
+
+    fn is_stateful(&self) -> bool {
+        true
+    }
+
+    fn restore(&self, rows: &dyn RowBatch) -> Result<(), String> {
+        let rows = rows
+            .as_any()
+            .downcast_ref::<Vec<TipAccountCandidateRow>>()
+            .ok_or_else(|| "stored rows are not tip account candidates".to_string())?;
+        self.tracker.lock().unwrap().restore(rows);
+        Ok(())
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch> {
+        let mut tracker = self.tracker.lock().unwrap();
+        let counted = tracker.observe_block(block.transactions, block.slot, block.validator_key);
+        if !counted || block.slot < self.last_flush_slot.load(Ordering::Relaxed) + TIP_CANDIDATE_FLUSH_INTERVAL {
+            return Box::new(Vec::<TipAccountCandidateRow>::new());
+        }
+
+        self.last_flush_slot.store(block.slot, Ordering::Relaxed);
+        tracker.prune(block.slot);
+        let candidates = tracker.candidates();
+        if !candidates.is_empty() {
+            println!(
+                "Recorded {} tip account candidates ({} unknown recipients tracked)",
+                candidates.len(),
+                tracker.tracked_recipients()
+            );
+        }
+        Box::new(candidates)
+    }
+}
@@ -0,0 +1,88 @@
+use clickhouse::Row;
+use serde::{Deserialize, Serialize};
+use super::types::Transaction;
+use super::utils::{get_primary_program, get_program_name, get_transaction_type};
+use super::registry::{Analyzer, BlockContext, RowBatch};
+
+#[derive(Debug, Row, Serialize, Deserialize)]
+pub struct TransactionRow {
+    pub slot: u64,
+    pub block_time: u64,
+    pub position: u32,
+    pub signature: String,
+    pub fee_payer: String,
+    pub fee: u64,
+    pub base_fee: u64,
+    pub priority_fee: u64,
+    pub compute_unit_price: u64,
+    pub compute_unit_limit: u32,
+    pub compute_units_consumed: u64,
+    pub tip_amount: u64,
+    pub tip_recipient: String,
+    pub tip_via_cpi: u8,
+    pub landing_service: String,
+    pub primary_program: String,
+    pub program_name: String,
+    pub transaction_type: String,
+    pub failed: u8,
+}
+
+/// One row per transaction, so questions the block-level tables cannot answer can still be queried
+pub fn analyze_transactions(
+    transactions: &[Transaction],
+    block_time: u64,
+) -> Vec<TransactionRow> {
+    transactions
+        .iter()
+        .map(|tx| {
+            let primary_program = get_primary_program(&tx.program_ids).unwrap_or_default();
+            let program_name = if primary_program.is_empty() { String::new() } else { get_program_name(&primary_program) };
+            TransactionRow {
+                slot: tx.slot,
+                block_time,
+                position: tx.position,
+                signature: tx.signature.clone(),
+                fee_payer: tx.fee_payer.clone(),
+                fee: tx.fee,
+                base_fee: tx.base_fee,
+                priority_fee: tx.priority_fee,
+                compute_unit_price: tx.compute_unit_price,
+                compute_unit_limit: tx.compute_unit_limit,
+                compute_units_consumed: tx.compute_units_consumed,
+                tip_amount: tx.tip_amount,
+                tip_recipient: tx.tip_recipient.clone(),
+                tip_via_cpi: tx.tip_via_cpi,
+                landing_service: tx.landing_service.clone(),
+                primary_program,
+                program_name,
+                transaction_type: get_transaction_type(tx),
+                failed: tx.failed,
+            }
+        })
+        .collect()
+}
+
+/// Off by default: one row per transaction is far more data than the block-level tables
+pub struct TransactionsAnalyzer;
+
+impl Analyzer for TransactionsAnalyzer {
+    fn name(&self) -> &'static str {
+        "transactions"
+    }
+
+    fn table(&self) -> &'static str {
+        "transactions"
+    }
+
+    fn schema(&self) -> &'static str {
+        include_str!("../../clickhouse/schema_transactions.sql")
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch> {
+        Box::new(analyze_transactions(block.transactions, block.block_time))
+    }
+}
@@ -1,9 +1,10 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[allow(dead_code)]
 pub struct Transaction {
     pub slot: u64,
     pub position: u32,
     pub signature: String,
+    pub fee_payer: String,
     pub recent_blockhash: String,
     pub fee: u64,
     pub failed: u8,
@@ -11,8 +12,24 @@ pub struct Transaction {
     pub is_vote: u8,
     pub is_system: u8,
     pub program_ids: Vec<String>,
-    pub landing_service: String,  // Empty string if unknown, otherwise service name
+    pub landing_service: String,  // Service name, "Plain Transfer" if SOL only went to unknown accounts, or "No Tip"
     pub tip_recipient: String,   // Address that received tip (if any)
     pub tip_amount: u64,          // Tip amount in lamports (0 if no tip)
+    pub tip_via_cpi: u8,          // 1 if the tip was paid from an inner instruction (CPI) rather than at the top level
+    pub tip_transfers: Vec<TipTransfer>,  // Every System transfer in the transaction, in instruction order
+    pub compute_units_consumed: u64,  // 0 if the RPC did not report it
+    pub base_fee: u64,            // 5000 lamports per signature
+    pub priority_fee: u64,        // fee minus base_fee
+    pub compute_unit_price: u64,  // Micro-lamports per CU from SetComputeUnitPrice (0 if not set)
+    pub compute_unit_limit: u32,  // CU limit from SetComputeUnitLimit (0 if not set)
 }
 
+
+/// A System Program transfer that may be a tip to a landing service
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TipTransfer {
+    pub recipient: String,
+    pub amount: u64,
+    pub via_cpi: u8,
+    pub landing_service: Option<String>,  // Set if the recipient is a known landing service account
+}
@@ -1,10 +1,19 @@
 use super::types::Transaction;
 
-#[allow(dead_code)]
 pub fn calculate_percentile(mut fees: Vec<u64>, percentile: f64) -> u64 {
     fees.sort();
     let index = ((fees.len() as f64) * percentile / 100.0) as usize;
-    fees.get(index.min(fees.len() - 1)).copied().unwrap_or(0)
+    fees.get(index.min(fees.len().saturating_sub(1))).copied().unwrap_or(0)
+}
+
+/// Mean of the non-zero values, or 0.0 if there are none (e.g. compute budget settings that were never set)
+pub fn mean_of_nonzero(values: impl Iterator<Item = u64>) -> f64 {
+    let (sum, count) = values.filter(|&v| v > 0).fold((0u64, 0u64), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum as f64 / count as f64
+    }
 }
 
 #[allow(dead_code)]
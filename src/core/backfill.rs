@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use clickhouse::Row;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use crate::analyzer::registry::AnalyzerRegistry;
+use super::block_fetcher::{store_block, BlockFetcher};
+use super::failed_slots::record_failed_slot;
+use super::pipeline::fetch_blocks;
+use super::sink::Sink;
+use super::slot_status::{classify_fetch_error, record_slot_status, SlotStatus};
+
+#[derive(Debug, Row, Serialize, Deserialize)]
+pub struct BackfillProgressRow {
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub last_slot: u64,
+}
+
+/// First slot still to be processed for the inclusive range `start_slot..=end_slot`,
+/// given the last slot recorded as handled. Returns `None` once the range is complete.
+pub fn resume_slot(start_slot: u64, end_slot: u64, last_slot: Option<u64>) -> Option<u64> {
+    let next = match last_slot {
+        Some(last) => last.saturating_add(1).max(start_slot),
+        None => start_slot,
+    };
+    if next > end_slot {
+        None
+    } else {
+        Some(next)
+    }
+}
+
+async fn save_backfill_progress(sink: &dyn Sink, start_slot: u64, end_slot: u64, last_slot: u64) -> Result<(), Box<dyn std::error::Error>> {
+    sink.write("backfill_progress", &vec![BackfillProgressRow { start_slot, end_slot, last_slot }]).await.map_err(|e| e as Box<dyn std::error::Error>)?;
+    Ok(())
+}
+
+pub async fn run_backfill(
+    sink: &dyn Sink,
+    fetcher: Arc<BlockFetcher>,
+    start_slot: u64,
+    end_slot: u64,
+    concurrency: usize,
+    analyzers: &AnalyzerRegistry,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if start_slot > end_slot {
+        return Err(format!("Invalid backfill range: start slot {} is after end slot {}", start_slot, end_slot).into());
+    }
+
+    let last_slot = sink.backfill_progress(start_slot, end_slot).await;
+    let first_slot = match resume_slot(start_slot, end_slot, last_slot) {
+        Some(slot) => slot,
+        None => {
+            println!("Backfill of slots {} to {} is already complete", start_slot, end_slot);
+            return Ok(());
+        }
+    };
+
+    if first_slot > start_slot {
+        println!("Resuming backfill of slots {} to {} from slot {}", start_slot, end_slot, first_slot);
+    } else {
+        println!("Starting backfill of slots {} to {}", start_slot, end_slot);
+    }
+
+    let total = end_slot - start_slot + 1;
+    let mut failed = 0u64;
+
+    let mut blocks = Box::pin(fetch_blocks(Arc::clone(&fetcher), first_slot..=end_slot, concurrency));
+    while let Some((slot, fetched)) = blocks.next().await {
+        let (status, result) = match fetched {
+            Ok(block) => (SlotStatus::Produced, store_block(sink, &block, analyzers, fetcher.retry_policy()).await),
+            Err(e) => (classify_fetch_error(&e), Err(e)),
+        };
+        let error = match result {
+            Ok(_) => {
+                println!("Block {} backfilled ({}/{})", slot, slot - start_slot + 1, total);
+                String::new()
+            }
+            // Skipped leader slots have no block, so they are not failures
+            Err(e) if status == SlotStatus::Skipped => {
+                println!("Slot {} was skipped by its leader ({}/{})", slot, slot - start_slot + 1, total);
+                e.to_string()
+            }
+            Err(e) => {
+                eprintln!("WARNING: Failed to backfill block {}: {}", slot, e);
+                record_failed_slot(sink, slot, &e, fetcher.retry_policy().attempts_for(&e)).await;
+                failed += 1;
+                e.to_string()
+            }
+        };
+        record_slot_status(sink, &fetcher, slot, status, error).await;
+        save_backfill_progress(sink, start_slot, end_slot, slot).await?;
+    }
+
+    sink.flush().await.map_err(|e| e as Box<dyn std::error::Error>)?;
+    if failed > 0 {
+        println!("Backfill of slots {} to {} complete ({} slots failed, run `retry-failed` to try them again)", start_slot, end_slot, failed);
+    } else {
+        println!("Backfill of slots {} to {} complete", start_slot, end_slot);
+    }
+    Ok(())
+}
@@ -1,71 +1,52 @@
-use clickhouse::Client;
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::commitment_config::CommitmentConfig;
+use crate::analyzer::registry::{AnalyzerRegistry, BlockContext, RowBatch};
 use crate::analyzer::types::Transaction;
-use crate::analyzer::bundling::analyze_bundling;
-use crate::analyzer::fee_landscape::analyze_fee_landscape;
-use crate::analyzer::program_fee::analyze_program_fees;
-use crate::analyzer::fee_by_type::analyze_fee_by_type;
-
-async fn get_validator_key(rpc_client: &RpcClient, slot: u64, alchemy_key: &str) -> String {
-    match rpc_client.get_slot_leaders(slot, 1) {
-        Ok(leaders) if !leaders.is_empty() => leaders[0].to_string(),
-        Err(_) | Ok(_) => {
-            // Fallback: try Alchemy API
-            let alchemy_url = format!("https://solana-mainnet.g.alchemy.com/v2/{}", alchemy_key);
-            let alchemy_client = RpcClient::new_with_commitment(alchemy_url, CommitmentConfig::finalized());
-            
-            match alchemy_client.get_slot_leaders(slot, 1) {
-                Ok(leaders) if !leaders.is_empty() => leaders[0].to_string(),
-                Err(_) | Ok(_) => String::new(),
-            }
-        }
+use super::config::AnalysisConfig;
+use super::ingest_error::IngestError;
+
+/// Built-in analyzers with the enable/disable choices from the config applied
+pub fn build_registry(analysis: &AnalysisConfig) -> Result<AnalyzerRegistry, String> {
+    let mut registry = AnalyzerRegistry::builtin();
+    if analysis.store_transactions {
+        registry.set_enabled("transactions", true)?;
     }
-}
-
-async fn insert_rows<T: clickhouse::Row + serde::Serialize>(client: &Client, table: &str, rows: &[T]) -> Result<(), Box<dyn std::error::Error>> {
-    if rows.is_empty() {
-        return Ok(());
+    for name in &analysis.enabled_analyzers {
+        registry.set_enabled(name, true)?;
     }
-    let mut inserter = client.insert(table)?;
-    for row in rows {
-        inserter.write(row).await?;
+    for name in &analysis.disabled_analyzers {
+        if analysis.enabled_analyzers.contains(name) {
+            return Err(format!("analyzer '{}' is both enabled and disabled", name));
+        }
+        registry.set_enabled(name, false)?;
     }
-    inserter.end().await?;
-    Ok(())
+    Ok(registry)
 }
 
-pub async fn analyze_block(
-    client: &Client,
-    rpc_client: &RpcClient,
+/// An analyzer's table and the rows it produced for one block
+pub type TableRows = (&'static str, Box<dyn RowBatch>);
+
+/// Run every enabled analyzer over a block, returning each analyzer's table and rows.
+/// Stateful analyzers see the block exactly once per call, so the rows are computed once and
+/// only their writes are retried.
+pub fn analyze_block(
     slot: u64,
-    blockhash: String,
+    blockhash: &str,
     block_time: u64,
     transactions: &[Transaction],
-    alchemy_key: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+    validator_key: Option<&str>,
+    analyzers: &AnalyzerRegistry,
+) -> Result<Vec<TableRows>, IngestError> {
     if transactions.is_empty() {
-        return Err("No transactions found in block".into());
+        return Err(IngestError::Decode("No transactions found in block".to_string()));
     }
 
-    let validator_key = get_validator_key(rpc_client, slot, alchemy_key).await;
+    let block = BlockContext {
+        slot,
+        blockhash,
+        block_time,
+        transactions,
+        validator_key,
+    };
 
-    // Bundling analysis
-    let bundling_row = analyze_bundling(transactions, slot, blockhash.clone(), block_time, validator_key.clone());
-    insert_rows(client, "bundling_analysis", &[bundling_row]).await?;
-
-    // Fee landscape analysis
-    let fee_row = analyze_fee_landscape(transactions, slot, block_time);
-    insert_rows(client, "fee_landscape", &[fee_row]).await?;
-
-    // Program fee analysis
-    let program_fee_rows = analyze_program_fees(transactions, slot, block_time);
-    insert_rows(client, "program_fee_analysis", &program_fee_rows).await?;
-
-    // Fee by transaction type analysis
-    let fee_by_type_rows = analyze_fee_by_type(transactions, slot, block_time);
-    insert_rows(client, "fee_by_transaction_type", &fee_by_type_rows).await?;
-
-    Ok(())
+    // Each analyzer's rows are written to its own table in a single insert
+    Ok(analyzers.enabled().map(|analyzer| (analyzer.table(), analyzer.analyze(&block))).collect())
 }
-
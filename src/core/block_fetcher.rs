@@ -1,14 +1,20 @@
-use clickhouse::Client;
 use clickhouse::Row;
 use serde::{Deserialize, Serialize};
-use solana_client::rpc_client::RpcClient;
 use solana_client::rpc_config::RpcBlockConfig;
-use solana_sdk::commitment_config::CommitmentConfig;
-use solana_transaction_status::UiTransactionEncoding;
+use solana_transaction_status::{UiConfirmedBlock, UiTransactionEncoding};
+use crate::analyzer::registry::{AnalyzerRegistry, RowBatch};
+use crate::analyzer::types::Transaction;
+use std::sync::Arc;
 use super::block_analyzer::analyze_block;
+use super::ingest_error::IngestError;
+use super::leader_schedule::{leader_from_rewards, LeaderScheduleCache};
+use super::replay::BlockRecorder;
+use super::retry::RetryPolicy;
+use super::rpc_pool::RpcPool;
+use super::sink::Sink;
 use super::transaction_parser::parse_transactions;
 
-#[derive(Debug, Row, Serialize, Deserialize)]
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
 pub struct BlockRow {
     pub slot: u64,
     pub parent_slot: u64,
@@ -18,61 +24,181 @@ pub struct BlockRow {
     pub transaction_count: u32,
 }
 
-pub async fn fetch_and_process_block(
-    client: &Client,
-    rpc_client: &RpcClient,
+/// A block that has been fetched and parsed, ready to be analyzed and stored
+#[derive(Debug)]
+pub struct FetchedBlock {
+    pub row: BlockRow,
+    pub transactions: Vec<Transaction>,
+    pub validator_key: Option<String>,  // None if the leader could not be resolved
+}
+
+/// Fetches blocks from the RPC pool and resolves their leaders
+pub struct BlockFetcher {
+    rpc_pool: Arc<RpcPool>,
+    leader_schedule: LeaderScheduleCache,
+    encoding: UiTransactionEncoding,
+    recorder: Option<BlockRecorder>,
+    retry_policy: RetryPolicy,
+}
+
+impl BlockFetcher {
+    pub fn new(rpc_pool: Arc<RpcPool>, encoding: UiTransactionEncoding) -> Self {
+        BlockFetcher {
+            rpc_pool,
+            leader_schedule: LeaderScheduleCache::new(),
+            encoding,
+            recorder: None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Save every fetched `getBlock` response with `recorder`, so the run can be replayed offline
+    pub fn with_recorder(mut self, recorder: BlockRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn rpc_pool(&self) -> &RpcPool {
+        &self.rpc_pool
+    }
+
+    /// Leader the schedule assigned to `slot`, whether or not it produced a block
+    pub async fn scheduled_leader(&self, slot: u64) -> Option<String> {
+        self.leader_schedule.get_leader(&self.rpc_pool, slot).await
+    }
+
+    pub async fn epoch(&self, slot: u64) -> Option<u64> {
+        self.leader_schedule.get_epoch(&self.rpc_pool, slot).await
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Fetch and parse a single block. This touches only the RPC, so many of these can run concurrently.
+    /// If an endpoint fails, the slot is retried on the next endpoint in the pool, and once every
+    /// endpoint failed, the whole pool is tried again as the retry policy allows.
+    pub async fn fetch_block(&self, slot: u64) -> Result<FetchedBlock, IngestError> {
+        self.retry_policy
+            .retry(slot, || fetch_block(&self.rpc_pool, &self.leader_schedule, self.encoding, self.recorder.as_ref(), slot))
+            .await
+    }
+
+    /// `fetch_block` for a slot at the chain tip. A block the RPC does not have yet is returned as
+    /// `BlockNotAvailable` without retrying, so it does not hold up the blocks after it and is
+    /// fetched again on the next poll.
+    pub async fn fetch_tip_block(&self, slot: u64) -> Result<FetchedBlock, IngestError> {
+        self.retry_policy
+            .retry_if(
+                slot,
+                |e| !matches!(e, IngestError::BlockNotAvailable(_)),
+                || fetch_block(&self.rpc_pool, &self.leader_schedule, self.encoding, self.recorder.as_ref(), slot),
+            )
+            .await
+    }
+}
+
+async fn fetch_block(
+    rpc_pool: &RpcPool,
+    leader_schedule: &LeaderScheduleCache,
+    encoding: UiTransactionEncoding,
+    recorder: Option<&BlockRecorder>,
     slot: u64,
-    alchemy_key: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<FetchedBlock, IngestError> {
     // Fetch block
-    let block = rpc_client.get_block_with_config(
+    let block = rpc_pool.get_block_with_config(
         slot,
         RpcBlockConfig {
-            encoding: Some(UiTransactionEncoding::JsonParsed),
+            encoding: Some(encoding),
             transaction_details: Some(solana_transaction_status::TransactionDetails::Full),
             rewards: Some(true),
-            commitment: Some(CommitmentConfig::finalized()),
+            commitment: Some(rpc_pool.commitment()),
             max_supported_transaction_version: Some(0),
         },
-    )?;
+    ).await.map_err(|e| IngestError::from_client_error(slot, e))?;
+
+    if let Some(recorder) = recorder {
+        if let Err(e) = recorder.record(slot, &block) {
+            eprintln!("WARNING: Failed to record block {}: {}", slot, e);
+        }
+    }
+
+    // Prefer the cached leader schedule, falling back to the fee reward paid to the block producer
+    let validator_key = match leader_schedule.get_leader(rpc_pool, slot).await {
+        Some(leader) => Some(leader),
+        None => block.rewards.as_deref().and_then(leader_from_rewards),
+    };
+    if validator_key.is_none() {
+        eprintln!("WARNING: Could not resolve the leader of slot {}", slot);
+    }
+
+    parse_block(slot, &block, validator_key)
+}
 
+/// Parse a `getBlock` response for `slot` into a block ready to be stored
+pub fn parse_block(slot: u64, block: &UiConfirmedBlock, validator_key: Option<String>) -> Result<FetchedBlock, IngestError> {
     let block_time = block.block_time.unwrap_or(0) as u64;
     let tx_count = block.transactions.as_ref().map(|v| v.len()).unwrap_or(0) as u32;
-    let blockhash = block.blockhash.clone();
 
-    // Store block
-    let block_row = BlockRow {
+    let row = BlockRow {
         slot,
         parent_slot: block.parent_slot,
-        blockhash: blockhash.clone(),
+        blockhash: block.blockhash.clone(),
         previous_blockhash: block.previous_blockhash.clone(),
         block_time,
         transaction_count: tx_count,
     };
 
-    let mut inserter = client.insert("blocks")?;
-    inserter.write(&block_row).await?;
-    inserter.end().await?;
-
     // Parse transactions
     let transactions = if let Some(ref block_transactions) = block.transactions {
         parse_transactions(block_transactions, slot)
     } else {
-        return Err("No transactions in block".into());
+        return Err(IngestError::Decode("No transactions in block".to_string()));
     };
 
-    // Analyze block
-    analyze_block(client, rpc_client, slot, blockhash, block_time, &transactions, alchemy_key).await?;
+    Ok(FetchedBlock { row, transactions, validator_key })
+}
+
+/// Store a fetched block and its analysis rows. Blocks must be stored in slot order.
+/// The analyzers run once; a write that fails is attempted again as `retry_policy` allows,
+/// without repeating the analysis or the writes that already succeeded.
+pub async fn store_block(sink: &dyn Sink, block: &FetchedBlock, analyzers: &AnalyzerRegistry, retry_policy: &RetryPolicy) -> Result<(), IngestError> {
+    let slot = block.row.slot;
+    write_rows(sink, slot, "blocks", &vec![block.row.clone()], retry_policy).await?;
+
+    let batches = analyze_block(
+        slot,
+        &block.row.blockhash,
+        block.row.block_time,
+        &block.transactions,
+        block.validator_key.as_deref(),
+        analyzers,
+    )?;
+    for (table, rows) in &batches {
+        write_rows(sink, slot, table, rows.as_ref(), retry_policy).await?;
+    }
 
     Ok(())
 }
 
-pub fn should_skip_old_slot(err_str: &str, slot: u64, current_slot: u64) -> bool {
-    if err_str.contains("cleaned up") || err_str.contains("does not exist") {
-        if slot < current_slot.saturating_sub(100) {
-            return true;
-        }
-    }
-    false
+async fn write_rows(sink: &dyn Sink, slot: u64, table: &str, rows: &dyn RowBatch, retry_policy: &RetryPolicy) -> Result<(), IngestError> {
+    retry_policy
+        .retry(slot, || async { sink.write(table, rows).await.map_err(IngestError::Sink) })
+        .await
 }
 
+/// Slots this close to the chain tip may not have reached every RPC node yet
+pub const TIP_SLOTS: u64 = 10;
+
+pub fn is_at_tip(slot: u64, current_slot: u64) -> bool {
+    slot.saturating_add(TIP_SLOTS) > current_slot
+}
+
+pub fn should_skip_old_slot(err: &IngestError, slot: u64, current_slot: u64) -> bool {
+    matches!(err, IngestError::SlotPruned(_)) && slot < current_slot.saturating_sub(100)
+}
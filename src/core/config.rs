@@ -0,0 +1,472 @@
+use std::path::{Path, PathBuf};
+use clap::Args;
+use serde::Deserialize;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_transaction_status::UiTransactionEncoding;
+
+/// Config file read when `--config` is not given, if it exists
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rpc: RpcConfig,
+    pub clickhouse: ClickHouseConfig,
+    pub ingest: IngestConfig,
+    pub analysis: AnalysisConfig,
+    pub output: OutputConfig,
+    pub geyser: GeyserConfig,
+    pub retry: RetryConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcConfig {
+    pub endpoints: Vec<RpcEndpointConfig>,
+    pub commitment: String,  // "confirmed" or "finalized"
+    pub encoding: String,    // Transaction encoding requested from getBlock: "jsonParsed", "json" or "base64"
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcEndpointConfig {
+    pub url: String,
+    #[serde(default)]
+    pub priority: u32,  // Lower values are tried first
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClickHouseConfig {
+    pub url: String,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IngestConfig {
+    pub poll_interval_ms: u64,  // How long to wait before re-reading the chain tip when caught up
+    pub batch_size: u64,        // Maximum number of slots handled per streaming pass
+    pub concurrency: usize,     // Maximum number of getBlock requests in flight
+    pub record: Option<PathBuf>,  // Save every fetched getBlock response here (a directory, or a .jsonl archive)
+    pub subscription: String,     // "none" to poll the chain tip, or "slot" / "block" to be notified over WebSocket
+    pub websocket_url: Option<String>,  // PubSub endpoint, derived from the first RPC endpoint if not set
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnalysisConfig {
+    pub landing_services_file: Option<PathBuf>,  // Landing service registry to use instead of the bundled one
+    pub store_transactions: bool,                // Also write every parsed transaction to the transactions table
+    pub transactions_retention_days: Option<u32>, // Delete transactions this many days after they were stored; kept forever if not set
+    pub enabled_analyzers: Vec<String>,          // Analyzers to run in addition to those enabled by default
+    pub disabled_analyzers: Vec<String>,         // Analyzers to skip
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub sink: String,        // "clickhouse", "parquet" or "jsonl"
+    pub directory: PathBuf,  // Where the parquet and jsonl sinks write their files
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeyserConfig {
+    pub endpoint: Option<String>,  // Yellowstone gRPC endpoint streamed by the geyser command
+    pub x_token: Option<String>,   // Sent as the x-token header, for endpoints that require one
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: u32,         // Attempts per slot before it is written to failed_slots
+    pub base_delay_ms: u64,        // Delay before the first retry, doubled for every further one
+    pub max_delay_ms: u64,         // Upper bound of the delay between attempts
+    pub rate_limit_delay_ms: u64,  // Delay before the first retry of a rate limited request
+}
+
+/// PubSub subscription used to learn about new slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionKind {
+    Slot,   // slotSubscribe
+    Block,  // blockSubscribe (must be enabled on the validator)
+}
+
+/// Where block and analysis rows are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkKind {
+    ClickHouse,
+    Parquet,
+    Jsonl,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        RpcConfig {
+            endpoints: vec![RpcEndpointConfig {
+                url: "https://api.mainnet-beta.solana.com".to_string(),
+                priority: 0,
+            }],
+            commitment: "finalized".to_string(),
+            encoding: "jsonParsed".to_string(),
+        }
+    }
+}
+
+impl Default for ClickHouseConfig {
+    fn default() -> Self {
+        ClickHouseConfig {
+            url: "http://localhost:8123".to_string(),
+            user: "default".to_string(),
+            password: "solana123".to_string(),
+            database: "solana".to_string(),
+        }
+    }
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        IngestConfig {
+            poll_interval_ms: 2000,
+            batch_size: 100,
+            concurrency: 8,
+            record: None,
+            subscription: "none".to_string(),
+            websocket_url: None,
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            rate_limit_delay_ms: 2_000,
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig {
+            sink: "clickhouse".to_string(),
+            directory: PathBuf::from("output"),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_toml_str(contents: &str) -> Result<Self, String> {
+        toml::from_str(contents).map_err(|e| e.to_string())
+    }
+
+    /// Load the config file at `path`, or `config.toml` if present, falling back to defaults
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => PathBuf::from(DEFAULT_CONFIG_PATH),
+            None => return Ok(Config::default()),
+        };
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        Self::from_toml_str(&contents).map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rpc.endpoints.is_empty() {
+            return Err("at least one RPC endpoint must be configured".to_string());
+        }
+        if let Some(endpoint) = self.rpc.endpoints.iter().find(|e| e.url.trim().is_empty()) {
+            return Err(format!("RPC endpoint with priority {} has an empty url", endpoint.priority));
+        }
+        parse_commitment(&self.rpc.commitment)?;
+        parse_encoding(&self.rpc.encoding)?;
+        parse_sink(&self.output.sink)?;
+        if parse_subscription(&self.ingest.subscription)?.is_some() && self.websocket_url().is_none() {
+            return Err("ingest.websocket_url must be set when the first RPC endpoint is not an http(s) URL".to_string());
+        }
+        if self.ingest.batch_size == 0 {
+            return Err("ingest.batch_size must be greater than 0".to_string());
+        }
+        if self.ingest.concurrency == 0 {
+            return Err("ingest.concurrency must be greater than 0".to_string());
+        }
+        if self.retry.max_attempts == 0 {
+            return Err("retry.max_attempts must be greater than 0".to_string());
+        }
+        if self.analysis.transactions_retention_days == Some(0) {
+            return Err("analysis.transactions_retention_days must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// RPC endpoints in the order they should be tried
+    pub fn sorted_endpoints(&self) -> Vec<RpcEndpointConfig> {
+        let mut endpoints = self.rpc.endpoints.clone();
+        endpoints.sort_by_key(|e| e.priority);
+        endpoints
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        parse_commitment(&self.rpc.commitment).unwrap_or_else(|_| CommitmentConfig::finalized())
+    }
+
+    pub fn encoding(&self) -> UiTransactionEncoding {
+        parse_encoding(&self.rpc.encoding).unwrap_or(UiTransactionEncoding::JsonParsed)
+    }
+
+    pub fn sink(&self) -> SinkKind {
+        parse_sink(&self.output.sink).unwrap_or(SinkKind::ClickHouse)
+    }
+
+    pub fn subscription(&self) -> Option<SubscriptionKind> {
+        parse_subscription(&self.ingest.subscription).unwrap_or(None)
+    }
+
+    /// The configured PubSub URL, or the highest-priority RPC endpoint with a WebSocket scheme
+    pub fn websocket_url(&self) -> Option<String> {
+        if let Some(ref url) = self.ingest.websocket_url {
+            return Some(url.clone());
+        }
+        let endpoint = self.sorted_endpoints().into_iter().next()?;
+        if let Some(rest) = endpoint.url.strip_prefix("https://") {
+            Some(format!("wss://{}", rest))
+        } else {
+            endpoint.url.strip_prefix("http://").map(|rest| format!("ws://{}", rest))
+        }
+    }
+}
+
+/// Hide a password in startup output, only showing whether one is set
+pub fn redact_password(password: &str) -> &'static str {
+    if password.is_empty() {
+        "(none)"
+    } else {
+        "********"
+    }
+}
+
+/// Parse a commitment level usable with `getBlock` (which rejects "processed")
+pub fn parse_commitment(level: &str) -> Result<CommitmentConfig, String> {
+    match level.parse::<CommitmentLevel>() {
+        Ok(CommitmentLevel::Confirmed) => Ok(CommitmentConfig::confirmed()),
+        Ok(CommitmentLevel::Finalized) => Ok(CommitmentConfig::finalized()),
+        _ => Err(format!("unsupported commitment level '{}', expected 'confirmed' or 'finalized'", level)),
+    }
+}
+
+/// Parse a transaction encoding the parser can analyze fully
+pub fn parse_encoding(encoding: &str) -> Result<UiTransactionEncoding, String> {
+    match encoding {
+        "jsonParsed" => Ok(UiTransactionEncoding::JsonParsed),
+        "json" => Ok(UiTransactionEncoding::Json),
+        "base64" => Ok(UiTransactionEncoding::Base64),
+        _ => Err(format!("unsupported encoding '{}', expected 'jsonParsed', 'json' or 'base64'", encoding)),
+    }
+}
+
+pub fn parse_sink(sink: &str) -> Result<SinkKind, String> {
+    match sink {
+        "clickhouse" => Ok(SinkKind::ClickHouse),
+        "parquet" => Ok(SinkKind::Parquet),
+        "jsonl" => Ok(SinkKind::Jsonl),
+        _ => Err(format!("unsupported sink '{}', expected 'clickhouse', 'parquet' or 'jsonl'", sink)),
+    }
+}
+
+pub fn parse_subscription(subscription: &str) -> Result<Option<SubscriptionKind>, String> {
+    match subscription {
+        "none" => Ok(None),
+        "slot" => Ok(Some(SubscriptionKind::Slot)),
+        "block" => Ok(Some(SubscriptionKind::Block)),
+        _ => Err(format!("unsupported subscription '{}', expected 'none', 'slot' or 'block'", subscription)),
+    }
+}
+
+/// Command line and environment overrides, applied on top of the config file
+#[derive(Debug, Default, Args)]
+pub struct ConfigArgs {
+    /// Path to a TOML config file (defaults to ./config.toml if it exists)
+    #[arg(long, global = true, env = "SOLANA_ANALYZER_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// RPC endpoint URL; repeat or comma-separate to give several, in priority order
+    #[arg(long = "rpc-url", global = true, env = "RPC_URLS", value_delimiter = ',')]
+    pub rpc_urls: Vec<String>,
+
+    /// Commitment level used for RPC requests (confirmed or finalized)
+    #[arg(long, global = true, env = "RPC_COMMITMENT")]
+    pub commitment: Option<String>,
+
+    /// Transaction encoding requested from getBlock (jsonParsed, json or base64)
+    #[arg(long, global = true, env = "RPC_ENCODING")]
+    pub encoding: Option<String>,
+
+    /// Where rows are written (clickhouse, parquet or jsonl)
+    #[arg(long, global = true, env = "OUTPUT_SINK")]
+    pub sink: Option<String>,
+
+    /// Directory the parquet and jsonl sinks write to
+    #[arg(long, global = true, env = "OUTPUT_DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    #[arg(long, global = true, env = "CLICKHOUSE_URL")]
+    pub clickhouse_url: Option<String>,
+
+    #[arg(long, global = true, env = "CLICKHOUSE_USER")]
+    pub clickhouse_user: Option<String>,
+
+    #[arg(long, global = true, env = "CLICKHOUSE_PASSWORD", hide_env_values = true)]
+    pub clickhouse_password: Option<String>,
+
+    #[arg(long, global = true, env = "CLICKHOUSE_DATABASE")]
+    pub clickhouse_database: Option<String>,
+
+    /// Milliseconds to wait before re-reading the chain tip when caught up
+    #[arg(long, global = true, env = "POLL_INTERVAL_MS")]
+    pub poll_interval_ms: Option<u64>,
+
+    /// Maximum number of slots handled per streaming pass
+    #[arg(long, global = true, env = "BATCH_SIZE")]
+    pub batch_size: Option<u64>,
+
+    /// Maximum number of blocks fetched from the RPC at the same time
+    #[arg(long, global = true, env = "CONCURRENCY")]
+    pub concurrency: Option<usize>,
+
+    /// Learn about new slots over WebSocket (slot or block) instead of only polling (none)
+    #[arg(long, global = true, env = "SLOT_SUBSCRIPTION")]
+    pub subscription: Option<String>,
+
+    /// PubSub WebSocket URL (defaults to the first RPC endpoint, with https:// turned into wss:// and http:// into ws://)
+    #[arg(long, global = true, env = "WEBSOCKET_URL")]
+    pub websocket_url: Option<String>,
+
+    /// Attempts per slot before it is written to the failed_slots table
+    #[arg(long, global = true, env = "RETRY_ATTEMPTS")]
+    pub retry_attempts: Option<u32>,
+
+    /// Save every fetched getBlock response to this directory (one <slot>.json per block) or .jsonl archive
+    #[arg(long, global = true, env = "RECORD_PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Yellowstone gRPC (Geyser) endpoint streamed by the geyser command
+    #[arg(long, global = true, env = "GEYSER_ENDPOINT")]
+    pub geyser_endpoint: Option<String>,
+
+    /// Token sent as the x-token header to the Geyser endpoint
+    #[arg(long, global = true, env = "GEYSER_X_TOKEN", hide_env_values = true)]
+    pub geyser_x_token: Option<String>,
+
+    /// Landing service registry file to load instead of the bundled one
+    #[arg(long, global = true, env = "LANDING_SERVICES_FILE")]
+    pub landing_services_file: Option<PathBuf>,
+
+    /// Store every parsed transaction in the transactions table
+    #[arg(long, global = true, env = "STORE_TRANSACTIONS")]
+    pub store_transactions: bool,
+
+    /// Days stored transactions are kept before ClickHouse deletes them
+    #[arg(long, global = true, env = "TRANSACTIONS_RETENTION_DAYS")]
+    pub transactions_retention_days: Option<u32>,
+
+    /// Analyzer to run in addition to the default ones; repeat or comma-separate to give several
+    #[arg(long = "enable-analyzer", global = true, env = "ENABLE_ANALYZERS", value_delimiter = ',')]
+    pub enabled_analyzers: Vec<String>,
+
+    /// Analyzer to skip; repeat or comma-separate to give several
+    #[arg(long = "disable-analyzer", global = true, env = "DISABLE_ANALYZERS", value_delimiter = ',')]
+    pub disabled_analyzers: Vec<String>,
+}
+
+impl ConfigArgs {
+    /// Load the config file and apply these overrides to it
+    pub fn resolve(&self) -> Result<Config, String> {
+        let mut config = Config::load(self.config.as_deref())?;
+        self.apply(&mut config);
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        if !self.rpc_urls.is_empty() {
+            config.rpc.endpoints = self
+                .rpc_urls
+                .iter()
+                .enumerate()
+                .map(|(idx, url)| RpcEndpointConfig { url: url.clone(), priority: idx as u32 })
+                .collect();
+        }
+        if let Some(ref commitment) = self.commitment {
+            config.rpc.commitment = commitment.clone();
+        }
+        if let Some(ref encoding) = self.encoding {
+            config.rpc.encoding = encoding.clone();
+        }
+        if let Some(ref sink) = self.sink {
+            config.output.sink = sink.clone();
+        }
+        if let Some(ref directory) = self.output_dir {
+            config.output.directory = directory.clone();
+        }
+        if let Some(ref url) = self.clickhouse_url {
+            config.clickhouse.url = url.clone();
+        }
+        if let Some(ref user) = self.clickhouse_user {
+            config.clickhouse.user = user.clone();
+        }
+        if let Some(ref password) = self.clickhouse_password {
+            config.clickhouse.password = password.clone();
+        }
+        if let Some(ref database) = self.clickhouse_database {
+            config.clickhouse.database = database.clone();
+        }
+        if let Some(poll_interval_ms) = self.poll_interval_ms {
+            config.ingest.poll_interval_ms = poll_interval_ms;
+        }
+        if let Some(batch_size) = self.batch_size {
+            config.ingest.batch_size = batch_size;
+        }
+        if let Some(concurrency) = self.concurrency {
+            config.ingest.concurrency = concurrency;
+        }
+        if let Some(ref subscription) = self.subscription {
+            config.ingest.subscription = subscription.clone();
+        }
+        if let Some(ref url) = self.websocket_url {
+            config.ingest.websocket_url = Some(url.clone());
+        }
+        if let Some(ref path) = self.record {
+            config.ingest.record = Some(path.clone());
+        }
+        if let Some(retry_attempts) = self.retry_attempts {
+            config.retry.max_attempts = retry_attempts;
+        }
+        if let Some(ref endpoint) = self.geyser_endpoint {
+            config.geyser.endpoint = Some(endpoint.clone());
+        }
+        if let Some(ref token) = self.geyser_x_token {
+            config.geyser.x_token = Some(token.clone());
+        }
+        if let Some(ref path) = self.landing_services_file {
+            config.analysis.landing_services_file = Some(path.clone());
+        }
+        if self.store_transactions {
+            config.analysis.store_transactions = true;
+        }
+        if let Some(days) = self.transactions_retention_days {
+            config.analysis.transactions_retention_days = Some(days);
+        }
+        if !self.enabled_analyzers.is_empty() {
+            config.analysis.enabled_analyzers = self.enabled_analyzers.clone();
+        }
+        if !self.disabled_analyzers.is_empty() {
+            config.analysis.disabled_analyzers = self.disabled_analyzers.clone();
+        }
+    }
+}
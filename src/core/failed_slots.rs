@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use clickhouse::Row;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use crate::analyzer::registry::AnalyzerRegistry;
+use super::block_fetcher::{store_block, BlockFetcher};
+use super::ingest_error::IngestError;
+use super::pipeline::fetch_blocks;
+use super::sink::Sink;
+use super::slot_status::{classify_fetch_error, record_slot_status, SlotStatus};
+
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct FailedSlotRow {
+    pub slot: u64,
+    pub error_kind: String,  // IngestError::kind of the last failure
+    pub error: String,
+    pub attempts: u32,
+    pub resolved: u8,        // 1 once the slot was recovered, replacing the failure
+}
+
+impl FailedSlotRow {
+    /// Unresolved failure of `slot` with `err` after `attempts` attempts
+    pub fn failure(slot: u64, err: &IngestError, attempts: u32) -> Self {
+        FailedSlotRow {
+            slot,
+            error_kind: err.kind().to_string(),
+            error: err.to_string(),
+            attempts,
+            resolved: 0,
+        }
+    }
+}
+
+async fn write_failed_slot(sink: &dyn Sink, row: FailedSlotRow) {
+    let slot = row.slot;
+    if let Err(e) = sink.write("failed_slots", &vec![row]).await {
+        eprintln!("WARNING: Failed to record failed slot {}: {}", slot, e);
+    }
+}
+
+/// Record that `slot` still failed with `err` after `attempts` attempts, so `retry-failed` can pick
+/// it up later. Failing to record it is only logged, so it never stops ingestion.
+pub async fn record_failed_slot(sink: &dyn Sink, slot: u64, err: &IngestError, attempts: u32) {
+    write_failed_slot(sink, FailedSlotRow::failure(slot, err, attempts)).await;
+}
+
+/// Fetch and store every unresolved slot of the `failed_slots` table again. Recovered slots are
+/// marked resolved; slots that fail again keep their row with the new error and attempt count.
+pub async fn run_retry_failed(
+    sink: &dyn Sink,
+    fetcher: Arc<BlockFetcher>,
+    concurrency: usize,
+    analyzers: &AnalyzerRegistry,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let failed = sink.failed_slots().await.map_err(|e| e as Box<dyn std::error::Error>)?;
+    if failed.is_empty() {
+        println!("No failed slots to retry");
+        return Ok(());
+    }
+    println!("Retrying {} failed slots", failed.len());
+
+    let previous_attempts: HashMap<u64, u32> = failed.iter().map(|row| (row.slot, row.attempts)).collect();
+    let policy = fetcher.retry_policy().clone();
+    let mut recovered = 0u64;
+    let mut blocks = Box::pin(fetch_blocks(Arc::clone(&fetcher), failed.iter().map(|row| row.slot).collect::<Vec<_>>(), concurrency));
+    while let Some((slot, fetched)) = blocks.next().await {
+        let (status, result) = match fetched {
+            Ok(block) => (SlotStatus::Produced, store_block(sink, &block, analyzers, &policy).await),
+            Err(e) => (classify_fetch_error(&e), Err(e)),
+        };
+        let attempts = previous_attempts.get(&slot).copied().unwrap_or(0);
+        match result {
+            // A skipped slot has no block to store, so it is resolved as well
+            Ok(_) | Err(IngestError::SlotSkipped(_)) => {
+                println!("Failed slot {} recovered ({})", slot, status.as_str());
+                recovered += 1;
+                let row = FailedSlotRow { slot, error_kind: String::new(), error: String::new(), attempts: attempts + 1, resolved: 1 };
+                write_failed_slot(sink, row).await;
+                record_slot_status(sink, &fetcher, slot, status, String::new()).await;
+            }
+            Err(e) => {
+                eprintln!("WARNING: Slot {} failed again: {}", slot, e);
+                record_failed_slot(sink, slot, &e, attempts + policy.attempts_for(&e)).await;
+                record_slot_status(sink, &fetcher, slot, status, e.to_string()).await;
+            }
+        }
+    }
+
+    sink.flush().await.map_err(|e| e as Box<dyn std::error::Error>)?;
+    println!("Retry complete: {} of {} failed slots recovered", recovered, failed.len());
+    Ok(())
+}
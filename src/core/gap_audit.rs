@@ -0,0 +1,145 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use futures::StreamExt;
+use crate::analyzer::registry::AnalyzerRegistry;
+use super::block_fetcher::{store_block, BlockFetcher};
+use super::failed_slots::record_failed_slot;
+use super::pipeline::fetch_blocks;
+use super::sink::Sink;
+use super::slot_status::{record_slot_status, slot_status_row, SlotStatus};
+
+// getBlocks rejects ranges wider than this many slots
+pub const GET_BLOCKS_MAX_RANGE: u64 = 500_000;
+
+/// Outcome of comparing the stored blocks of a slot range with the chain's block list
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GapReport {
+    pub missing: Vec<u64>,      // Slots with a block on chain that was never stored: our own failures
+    pub skipped: u64,           // Slots whose leader produced no block
+    pub not_on_chain: Vec<u64>, // Stored slots the chain no longer lists, e.g. a confirmed block on a dropped fork
+}
+
+/// Classify every slot of `start_slot..=end_slot`, given the slots the chain has blocks for and
+/// the slots that are stored. Slots outside the range are ignored.
+pub fn find_gaps(start_slot: u64, end_slot: u64, confirmed: &[u64], stored: &[u64]) -> GapReport {
+    let in_range = |slot: &&u64| (start_slot..=end_slot).contains(*slot);
+    let confirmed: BTreeSet<u64> = confirmed.iter().filter(in_range).copied().collect();
+    let stored: BTreeSet<u64> = stored.iter().filter(in_range).copied().collect();
+
+    GapReport {
+        missing: confirmed.difference(&stored).copied().collect(),
+        skipped: (end_slot - start_slot + 1) - confirmed.len() as u64,
+        not_on_chain: stored.difference(&confirmed).copied().collect(),
+    }
+}
+
+/// Slots with a block in `start_slot..=end_slot`, requested in chunks getBlocks accepts
+async fn confirmed_slots(fetcher: &BlockFetcher, start_slot: u64, end_slot: u64) -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+    let mut slots = Vec::new();
+    let mut chunk_start = start_slot;
+    while chunk_start <= end_slot {
+        let chunk_end = end_slot.min(chunk_start.saturating_add(GET_BLOCKS_MAX_RANGE - 1));
+        slots.extend(fetcher.rpc_pool().get_blocks(chunk_start, chunk_end).await?);
+        if chunk_end == u64::MAX {
+            break;
+        }
+        chunk_start = chunk_end + 1;
+    }
+    Ok(slots)
+}
+
+/// Audit `start_slot..=end_slot` for blocks that exist on chain but were never stored, and unless
+/// `report_only` is set, fetch and store them again.
+pub async fn run_gap_audit(
+    sink: &dyn Sink,
+    fetcher: Arc<BlockFetcher>,
+    start_slot: u64,
+    end_slot: u64,
+    concurrency: usize,
+    analyzers: &AnalyzerRegistry,
+    report_only: bool,
+) -> Result<GapReport, Box<dyn std::error::Error>> {
+    if start_slot > end_slot {
+        return Err(format!("Invalid audit range: start slot {} is after end slot {}", start_slot, end_slot).into());
+    }
+    // getBlocks lists no block past the tip, which would make every such slot look skipped
+    let current_slot = fetcher.rpc_pool().get_slot().await?;
+    if start_slot > current_slot {
+        return Err(format!("Invalid audit range: start slot {} is after the chain tip {}", start_slot, current_slot).into());
+    }
+    let end_slot = if end_slot > current_slot {
+        println!("End slot {} is after the chain tip, auditing up to slot {}", end_slot, current_slot);
+        current_slot
+    } else {
+        end_slot
+    };
+
+    println!("Auditing slots {} to {}", start_slot, end_slot);
+    let stored = sink.stored_slots(start_slot, end_slot).await.map_err(|e| e as Box<dyn std::error::Error>)?;
+    let confirmed = confirmed_slots(&fetcher, start_slot, end_slot).await?;
+    let report = find_gaps(start_slot, end_slot, &confirmed, &stored);
+
+    println!(
+        "{} slots: {} blocks stored, {} skipped by their leader, {} missing",
+        end_slot - start_slot + 1,
+        stored.len(),
+        report.skipped,
+        report.missing.len()
+    );
+    if !report.not_on_chain.is_empty() {
+        eprintln!(
+            "WARNING: {} stored blocks are not on chain (first: slot {}), they may be from a dropped fork",
+            report.not_on_chain.len(),
+            report.not_on_chain[0]
+        );
+    }
+    if report_only {
+        for slot in &report.missing {
+            println!("Missing block {}", slot);
+        }
+        return Ok(report);
+    }
+
+    // getBlocks is authoritative about skipped slots, whatever was recorded while fetching them
+    let confirmed: BTreeSet<u64> = confirmed.into_iter().collect();
+    let mut skipped = Vec::with_capacity(report.skipped as usize);
+    // The range and the block list are both in slot order, so they are walked together
+    let mut confirmed = confirmed.into_iter().peekable();
+    for slot in start_slot..=end_slot {
+        while confirmed.next_if(|&block_slot| block_slot < slot).is_some() {}
+        if confirmed.next_if_eq(&slot).is_none() {
+            skipped.push(slot_status_row(&fetcher, slot, SlotStatus::Skipped, String::new()).await);
+        }
+    }
+    sink.write("slot_status", &skipped).await.map_err(|e| e as Box<dyn std::error::Error>)?;
+
+    if !report.missing.is_empty() {
+        println!("Re-fetching {} missing blocks", report.missing.len());
+    }
+    let mut recovered = 0u64;
+    let mut blocks = Box::pin(fetch_blocks(Arc::clone(&fetcher), report.missing.clone(), concurrency));
+    while let Some((slot, fetched)) = blocks.next().await {
+        let result = match fetched {
+            Ok(block) => store_block(sink, &block, analyzers, fetcher.retry_policy()).await,
+            Err(e) => Err(e),
+        };
+        // The block is on chain, so the slot was produced even if it still could not be stored
+        let error = match result {
+            Ok(_) => {
+                println!("Missing block {} fetched and analyzed", slot);
+                recovered += 1;
+                String::new()
+            }
+            Err(e) => {
+                eprintln!("WARNING: Failed to re-fetch missing block {}: {}", slot, e);
+                record_failed_slot(sink, slot, &e, fetcher.retry_policy().attempts_for(&e)).await;
+                e.to_string()
+            }
+        };
+        record_slot_status(sink, &fetcher, slot, SlotStatus::Produced, error).await;
+    }
+
+    sink.flush().await.map_err(|e| e as Box<dyn std::error::Error>)?;
+    println!("Audit complete: {} of {} missing blocks recovered", recovered, report.missing.len());
+    Ok(report)
+}
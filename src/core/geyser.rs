@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use futures::StreamExt;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{CompiledInstruction, InstructionError};
+use solana_sdk::message::v0::{self, LoadedAddresses, MessageAddressTableLookup};
+use solana_sdk::message::{legacy, MessageHeader, VersionedMessage};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::reward_type::RewardType;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_transaction_status::{InnerInstruction, InnerInstructions, Reward, TransactionStatusMeta, UiTransactionStatusMeta};
+use tonic::codec::{ProstCodec, Streaming};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::MetadataValue;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::Status;
+use crate::analyzer::registry::AnalyzerRegistry;
+use super::block_fetcher::{store_block, BlockFetcher, BlockRow, FetchedBlock};
+use super::failed_slots::{record_failed_slot, FailedSlotRow};
+use super::geyser_proto::{self as proto, subscribe_update::UpdateOneof};
+use super::ingest_error::IngestError;
+use super::leader_schedule::leader_from_rewards;
+use super::rpc_pool::redact_url;
+use super::sink::Sink;
+use super::slot_status::{record_slot_status, slot_status_row, SlotStatus};
+use super::slot_subscription::{INITIAL_RECONNECT_DELAY, MAX_RECONNECT_DELAY};
+use super::transaction_parser::parse_decoded_transactions;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// Keeps idle connections alive through load balancers between blocks
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+const BLOCK_FILTER: &str = "blocks";
+
+/// Blocks with their transactions, streamed from a Yellowstone gRPC (Geyser) subscription
+pub struct GeyserBlocks {
+    updates: Streaming<proto::SubscribeUpdate>,
+}
+
+impl GeyserBlocks {
+    /// Connect to `endpoint` and subscribe to every block at `commitment`. `x_token` is sent as
+    /// the `x-token` header most hosted endpoints authenticate with.
+    pub async fn subscribe(endpoint: &str, x_token: Option<&str>, commitment: CommitmentConfig) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let channel = connect(endpoint).await?;
+        let mut grpc = tonic::client::Grpc::new(channel);
+        grpc.ready().await?;
+
+        let filter = proto::SubscribeRequestFilterBlocks {
+            account_include: Vec::new(),
+            include_transactions: Some(true),
+            include_accounts: Some(false),
+            include_entries: Some(false),
+        };
+        let commitment = if commitment.is_finalized() {
+            proto::CommitmentLevel::Finalized
+        } else {
+            proto::CommitmentLevel::Confirmed
+        };
+        let subscribe = proto::SubscribeRequest {
+            blocks: HashMap::from([(BLOCK_FILTER.to_string(), filter)]),
+            commitment: Some(commitment as i32),
+        };
+        // The request stream is left open; closing it would end the subscription
+        let requests = futures::stream::iter([subscribe]).chain(futures::stream::pending());
+        let mut request = tonic::Request::new(requests);
+        if let Some(token) = x_token {
+            request.metadata_mut().insert("x-token", MetadataValue::try_from(token)?);
+        }
+
+        let path = PathAndQuery::from_static(proto::SUBSCRIBE_PATH);
+        let codec = ProstCodec::<proto::SubscribeRequest, proto::SubscribeUpdate>::default();
+        let response = grpc.streaming(request, path, codec).await?;
+        Ok(GeyserBlocks { updates: response.into_inner() })
+    }
+
+    /// The next block, or None once the server ends the stream. Pings are skipped.
+    pub async fn next_block(&mut self) -> Result<Option<proto::SubscribeUpdateBlock>, Status> {
+        while let Some(update) = self.updates.message().await? {
+            if let Some(UpdateOneof::Block(block)) = update.update_oneof {
+                return Ok(Some(block));
+            }
+        }
+        Ok(None)
+    }
+}
+
+async fn connect(endpoint: &str) -> Result<Channel, tonic::transport::Error> {
+    let mut builder = Endpoint::from_shared(endpoint.to_string())?
+        .connect_timeout(CONNECT_TIMEOUT)
+        .http2_keep_alive_interval(KEEP_ALIVE_INTERVAL)
+        .keep_alive_while_idle(true);
+    if endpoint.starts_with("https://") {
+        builder = builder.tls_config(ClientTlsConfig::new())?;
+    }
+    builder.connect().await
+}
+
+/// Map a Geyser block into the same block row and transactions a `getBlock` response produces.
+/// The leader is taken from the fee reward, as nothing is read from the RPC.
+pub fn parse_geyser_block(block: &proto::SubscribeUpdateBlock) -> Result<FetchedBlock, IngestError> {
+    let slot = block.slot;
+    let mut transaction_infos: Vec<&proto::SubscribeUpdateTransactionInfo> = block.transactions.iter().collect();
+    // Transactions are collected as they execute, so their block order is restored from the index
+    transaction_infos.sort_by_key(|info| info.index);
+
+    let decoded: Vec<_> = transaction_infos
+        .iter()
+        .map(|info| (info.transaction.as_ref().and_then(decode_transaction), info.meta.as_ref().map(decode_meta)))
+        .collect();
+
+    let rewards: Vec<Reward> = block.rewards.iter().flat_map(|r| &r.rewards).map(decode_reward).collect();
+    let validator_key = leader_from_rewards(&rewards);
+    if validator_key.is_none() {
+        eprintln!("WARNING: Could not resolve the leader of slot {}", slot);
+    }
+
+    let row = BlockRow {
+        slot,
+        parent_slot: block.parent_slot,
+        blockhash: block.blockhash.clone(),
+        previous_blockhash: block.parent_blockhash.clone(),
+        block_time: block.block_time.as_ref().map(|t| t.timestamp).unwrap_or(0) as u64,
+        transaction_count: decoded.len() as u32,
+    };
+    let transactions = parse_decoded_transactions(&decoded, slot);
+
+    Ok(FetchedBlock { row, transactions, validator_key })
+}
+
+fn decode_pubkeys(keys: &[Vec<u8>]) -> Option<Vec<Pubkey>> {
+    keys.iter().map(|key| Pubkey::try_from(key.as_slice()).ok()).collect()
+}
+
+fn decode_transaction(transaction: &proto::Transaction) -> Option<VersionedTransaction> {
+    let message = transaction.message.as_ref()?;
+    let header = message.header.as_ref()?;
+    let header = MessageHeader {
+        num_required_signatures: header.num_required_signatures as u8,
+        num_readonly_signed_accounts: header.num_readonly_signed_accounts as u8,
+        num_readonly_unsigned_accounts: header.num_readonly_unsigned_accounts as u8,
+    };
+    let account_keys = decode_pubkeys(&message.account_keys)?;
+    let recent_blockhash = Hash::new_from_array(message.recent_blockhash.as_slice().try_into().ok()?);
+    let instructions = message
+        .instructions
+        .iter()
+        .map(|inst| CompiledInstruction {
+            program_id_index: inst.program_id_index as u8,
+            accounts: inst.accounts.clone(),
+            data: inst.data.clone(),
+        })
+        .collect();
+
+    let message = if message.versioned {
+        let address_table_lookups = message
+            .address_table_lookups
+            .iter()
+            .map(|lookup| {
+                Some(MessageAddressTableLookup {
+                    account_key: Pubkey::try_from(lookup.account_key.as_slice()).ok()?,
+                    writable_indexes: lookup.writable_indexes.clone(),
+                    readonly_indexes: lookup.readonly_indexes.clone(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        VersionedMessage::V0(v0::Message { header, account_keys, recent_blockhash, instructions, address_table_lookups })
+    } else {
+        VersionedMessage::Legacy(legacy::Message { header, account_keys, recent_blockhash, instructions })
+    };
+    let signatures = transaction
+        .signatures
+        .iter()
+        .map(|signature| Signature::try_from(signature.as_slice()).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(VersionedTransaction { signatures, message })
+}
+
+fn decode_meta(meta: &proto::TransactionStatusMeta) -> UiTransactionStatusMeta {
+    // Only whether a transaction failed is analyzed, so the bincode-encoded error is not decoded
+    let status = match meta.err {
+        Some(_) => Err(TransactionError::InstructionError(0, InstructionError::GenericError)),
+        None => Ok(()),
+    };
+    let inner_instructions = (!meta.inner_instructions_none).then(|| {
+        meta.inner_instructions
+            .iter()
+            .map(|inner| InnerInstructions {
+                index: inner.index as u8,
+                instructions: inner
+                    .instructions
+                    .iter()
+                    .map(|inst| InnerInstruction {
+                        instruction: CompiledInstruction {
+                            program_id_index: inst.program_id_index as u8,
+                            accounts: inst.accounts.clone(),
+                            data: inst.data.clone(),
+                        },
+                        stack_height: inst.stack_height,
+                    })
+                    .collect(),
+            })
+            .collect()
+    });
+    let loaded_addresses = LoadedAddresses {
+        writable: decode_pubkeys(&meta.loaded_writable_addresses).unwrap_or_default(),
+        readonly: decode_pubkeys(&meta.loaded_readonly_addresses).unwrap_or_default(),
+    };
+
+    TransactionStatusMeta {
+        status,
+        fee: meta.fee,
+        pre_balances: meta.pre_balances.clone(),
+        post_balances: meta.post_balances.clone(),
+        inner_instructions,
+        loaded_addresses,
+        compute_units_consumed: meta.compute_units_consumed,
+        ..TransactionStatusMeta::default()
+    }
+    .into()
+}
+
+fn decode_reward(reward: &proto::Reward) -> Reward {
+    let reward_type = match proto::RewardType::try_from(reward.reward_type) {
+        Ok(proto::RewardType::Fee) => Some(RewardType::Fee),
+        Ok(proto::RewardType::Rent) => Some(RewardType::Rent),
+        Ok(proto::RewardType::Staking) => Some(RewardType::Staking),
+        Ok(proto::RewardType::Voting) => Some(RewardType::Voting),
+        _ => None,
+    };
+    Reward {
+        pubkey: reward.pubkey.clone(),
+        lamports: reward.lamports,
+        post_balance: reward.post_balance,
+        reward_type,
+        commission: reward.commission.parse().ok(),
+    }
+}
+
+/// Store every block streamed from a Geyser endpoint, reconnecting with exponential backoff
+/// whenever the stream ends. Slots that may have been missed while disconnected are recorded as
+/// failed, like blocks that could not be stored, so `retry-failed` fetches them from the RPC.
+/// `fetcher` resolves the epoch and leader of each recorded slot.
+pub async fn run_geyser_stream(
+    sink: &dyn Sink,
+    endpoint: &str,
+    x_token: Option<&str>,
+    commitment: CommitmentConfig,
+    analyzers: &AnalyzerRegistry,
+    fetcher: &BlockFetcher,
+) {
+    println!("Starting Solana block fetcher & analyzer - Geyser streaming mode");
+    println!("Streaming blocks from {}\n", redact_url(endpoint));
+
+    let mut last_streamed_slot = sink.last_block_slot().await;
+    let mut delay = INITIAL_RECONNECT_DELAY;
+    loop {
+        // A subscription that delivered blocks starts the backoff over
+        if stream_blocks(sink, endpoint, x_token, commitment, analyzers, fetcher, &mut last_streamed_slot).await {
+            delay = INITIAL_RECONNECT_DELAY;
+        }
+        eprintln!("WARNING: Geyser stream from {} ended, reconnecting in {:?}", redact_url(endpoint), delay);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// Subscribe once and store blocks until the stream ends. Returns whether any block was received.
+async fn stream_blocks(
+    sink: &dyn Sink,
+    endpoint: &str,
+    x_token: Option<&str>,
+    commitment: CommitmentConfig,
+    analyzers: &AnalyzerRegistry,
+    fetcher: &BlockFetcher,
+    last_streamed_slot: &mut Option<u64>,
+) -> bool {
+    let mut blocks = match GeyserBlocks::subscribe(endpoint, x_token, commitment).await {
+        Ok(blocks) => blocks,
+        Err(e) => {
+            eprintln!("WARNING: Geyser subscription to {} failed: {}", redact_url(endpoint), e);
+            return false;
+        }
+    };
+    println!("Subscribed to blocks from {}", redact_url(endpoint));
+
+    let mut received = false;
+    loop {
+        let block = match blocks.next_block().await {
+            Ok(Some(block)) => block,
+            Ok(None) => return received,
+            Err(status) => {
+                eprintln!("WARNING: Geyser stream from {} failed: {}", redact_url(endpoint), status);
+                return received;
+            }
+        };
+        let slot = block.slot;
+        if let Some(last) = *last_streamed_slot {
+            if slot <= last {
+                continue;
+            }
+            // Within a stream every produced block is delivered, so only a new subscription can miss some
+            if !received && slot > last + 1 {
+                println!("Slots {}-{} were not streamed, recording them for retry-failed", last + 1, slot - 1);
+                record_missed_slots(sink, fetcher, last + 1, slot - 1).await;
+            }
+        }
+        received = true;
+
+        let result = match parse_geyser_block(&block) {
+            Ok(fetched) => store_block(sink, &fetched, analyzers, fetcher.retry_policy()).await,
+            Err(e) => Err(e),
+        };
+        // The block was streamed, so the slot was produced even if it could not be stored
+        match result {
+            Ok(_) => {
+                println!("Block {} streamed and analyzed", slot);
+                record_slot_status(sink, fetcher, slot, SlotStatus::Produced, String::new()).await;
+            }
+            Err(e) => {
+                eprintln!("WARNING: Failed to process block {}: {}", slot, e);
+                record_failed_slot(sink, slot, &e, fetcher.retry_policy().attempts_for(&e)).await;
+                record_slot_status(sink, fetcher, slot, SlotStatus::Produced, e.to_string()).await;
+            }
+        }
+        // A failed block is in failed_slots, so the stream moves past it either way
+        *last_streamed_slot = Some(slot);
+    }
+}
+
+/// Record `first_slot..=last_slot` as unavailable and failed. They may hold blocks the stream did
+/// not deliver, and `retry-failed` tells the produced ones from the skipped ones.
+async fn record_missed_slots(sink: &dyn Sink, fetcher: &BlockFetcher, first_slot: u64, last_slot: u64) {
+    let mut statuses = Vec::new();
+    let mut failures = Vec::new();
+    for slot in first_slot..=last_slot {
+        let err = IngestError::NotStreamed(slot);
+        statuses.push(slot_status_row(fetcher, slot, SlotStatus::Unavailable, err.to_string()).await);
+        failures.push(FailedSlotRow::failure(slot, &err, 0));
+    }
+    if let Err(e) = sink.write("failed_slots", &failures).await {
+        eprintln!("WARNING: Failed to record slots {}-{} as failed: {}", first_slot, last_slot, e);
+    }
+    if let Err(e) = sink.write("slot_status", &statuses).await {
+        eprintln!("WARNING: Failed to record status of slots {}-{}: {}", first_slot, last_slot, e);
+    }
+}
@@ -0,0 +1,230 @@
+// Messages of the Yellowstone gRPC `geyser.Geyser/Subscribe` call, written out by hand from
+// geyser.proto and solana-storage.proto (package solana.storage.ConfirmedBlock) so no protoc is
+// needed to build. Only the fields the block source reads are declared; tags match the upstream
+// definitions and prost skips every other field on the wire.
+
+use std::collections::HashMap;
+
+/// Full method path of the bidirectional streaming subscription
+pub const SUBSCRIBE_PATH: &str = "/geyser.Geyser/Subscribe";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum CommitmentLevel {
+    Processed = 0,
+    Confirmed = 1,
+    Finalized = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequest {
+    #[prost(map = "string, message", tag = "4")]
+    pub blocks: HashMap<String, SubscribeRequestFilterBlocks>,
+    #[prost(enumeration = "CommitmentLevel", optional, tag = "6")]
+    pub commitment: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequestFilterBlocks {
+    #[prost(string, repeated, tag = "1")]
+    pub account_include: Vec<String>,
+    #[prost(bool, optional, tag = "2")]
+    pub include_transactions: Option<bool>,
+    #[prost(bool, optional, tag = "3")]
+    pub include_accounts: Option<bool>,
+    #[prost(bool, optional, tag = "4")]
+    pub include_entries: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdate {
+    #[prost(string, repeated, tag = "1")]
+    pub filters: Vec<String>,
+    // Account, slot, transaction and entry updates are never requested
+    #[prost(oneof = "subscribe_update::UpdateOneof", tags = "5, 6")]
+    pub update_oneof: Option<subscribe_update::UpdateOneof>,
+}
+
+pub mod subscribe_update {
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum UpdateOneof {
+        #[prost(message, tag = "5")]
+        Block(super::SubscribeUpdateBlock),
+        #[prost(message, tag = "6")]
+        Ping(super::SubscribeUpdatePing),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdatePing {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdateBlock {
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    #[prost(string, tag = "2")]
+    pub blockhash: String,
+    #[prost(message, optional, tag = "3")]
+    pub rewards: Option<Rewards>,
+    #[prost(message, optional, tag = "4")]
+    pub block_time: Option<UnixTimestamp>,
+    #[prost(message, repeated, tag = "6")]
+    pub transactions: Vec<SubscribeUpdateTransactionInfo>,
+    #[prost(uint64, tag = "7")]
+    pub parent_slot: u64,
+    #[prost(string, tag = "8")]
+    pub parent_blockhash: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdateTransactionInfo {
+    #[prost(bytes = "vec", tag = "1")]
+    pub signature: Vec<u8>,
+    #[prost(bool, tag = "2")]
+    pub is_vote: bool,
+    #[prost(message, optional, tag = "3")]
+    pub transaction: Option<Transaction>,
+    #[prost(message, optional, tag = "4")]
+    pub meta: Option<TransactionStatusMeta>,
+    #[prost(uint64, tag = "5")]
+    pub index: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Transaction {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub signatures: Vec<Vec<u8>>,
+    #[prost(message, optional, tag = "2")]
+    pub message: Option<Message>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Message {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<MessageHeader>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub account_keys: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub recent_blockhash: Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub instructions: Vec<CompiledInstruction>,
+    #[prost(bool, tag = "5")]
+    pub versioned: bool,
+    #[prost(message, repeated, tag = "6")]
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageHeader {
+    #[prost(uint32, tag = "1")]
+    pub num_required_signatures: u32,
+    #[prost(uint32, tag = "2")]
+    pub num_readonly_signed_accounts: u32,
+    #[prost(uint32, tag = "3")]
+    pub num_readonly_unsigned_accounts: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageAddressTableLookup {
+    #[prost(bytes = "vec", tag = "1")]
+    pub account_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub writable_indexes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub readonly_indexes: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CompiledInstruction {
+    #[prost(uint32, tag = "1")]
+    pub program_id_index: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub accounts: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransactionStatusMeta {
+    #[prost(message, optional, tag = "1")]
+    pub err: Option<TransactionError>,
+    #[prost(uint64, tag = "2")]
+    pub fee: u64,
+    #[prost(uint64, repeated, tag = "3")]
+    pub pre_balances: Vec<u64>,
+    #[prost(uint64, repeated, tag = "4")]
+    pub post_balances: Vec<u64>,
+    #[prost(message, repeated, tag = "5")]
+    pub inner_instructions: Vec<InnerInstructions>,
+    #[prost(bool, tag = "10")]
+    pub inner_instructions_none: bool,
+    #[prost(bytes = "vec", repeated, tag = "12")]
+    pub loaded_writable_addresses: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "13")]
+    pub loaded_readonly_addresses: Vec<Vec<u8>>,
+    #[prost(uint64, optional, tag = "16")]
+    pub compute_units_consumed: Option<u64>,
+}
+
+/// A bincode-encoded `TransactionError`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransactionError {
+    #[prost(bytes = "vec", tag = "1")]
+    pub err: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InnerInstructions {
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub instructions: Vec<InnerInstruction>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InnerInstruction {
+    #[prost(uint32, tag = "1")]
+    pub program_id_index: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub accounts: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: Vec<u8>,
+    #[prost(uint32, optional, tag = "4")]
+    pub stack_height: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum RewardType {
+    Unspecified = 0,
+    Fee = 1,
+    Rent = 2,
+    Staking = 3,
+    Voting = 4,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Reward {
+    #[prost(string, tag = "1")]
+    pub pubkey: String,
+    #[prost(int64, tag = "2")]
+    pub lamports: i64,
+    #[prost(uint64, tag = "3")]
+    pub post_balance: u64,
+    #[prost(enumeration = "RewardType", tag = "4")]
+    pub reward_type: i32,
+    #[prost(string, tag = "5")]
+    pub commission: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Rewards {
+    #[prost(message, repeated, tag = "1")]
+    pub rewards: Vec<Reward>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UnixTimestamp {
+    #[prost(int64, tag = "1")]
+    pub timestamp: i64,
+}
@@ -0,0 +1,91 @@
+use std::fmt;
+use solana_client::client_error::ClientError;
+use super::rpc_pool::{is_block_not_available, is_rate_limited, is_slot_pruned, is_slot_skipped};
+use super::sink::SinkError;
+
+/// Why a slot could not be fetched, analyzed or stored
+#[derive(Debug)]
+pub enum IngestError {
+    Rpc(Box<dyn std::error::Error + Send + Sync>),  // Transport failure, or an RPC error not covered below
+    SlotSkipped(u64),                               // The leader produced no block
+    SlotPruned(u64),                                // The block is older than the node's ledger history
+    BlockNotAvailable(u64),                         // The node does not have the block yet
+    #[cfg_attr(not(feature = "geyser"), allow(dead_code))]
+    NotStreamed(u64),                               // The Geyser stream may have missed the slot while disconnected
+    RateLimited(Box<ClientError>),                  // Every endpoint rejected the request with a rate limit
+    Decode(String),                                 // The block could not be turned into transactions
+    Sink(SinkError),                                // The block or its analysis rows could not be written
+}
+
+impl IngestError {
+    /// Classify an RPC error returned for `slot`
+    pub fn from_client_error(slot: u64, err: ClientError) -> Self {
+        if is_slot_skipped(&err) {
+            IngestError::SlotSkipped(slot)
+        } else if is_slot_pruned(&err) {
+            IngestError::SlotPruned(slot)
+        } else if is_block_not_available(&err) {
+            IngestError::BlockNotAvailable(slot)
+        } else if is_rate_limited(&err) {
+            IngestError::RateLimited(Box::new(err))
+        } else {
+            IngestError::Rpc(Box::new(err))
+        }
+    }
+
+    /// Short name of the error kind, as stored in the failed_slots table
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IngestError::Rpc(_) => "rpc",
+            IngestError::SlotSkipped(_) => "slot_skipped",
+            IngestError::SlotPruned(_) => "slot_pruned",
+            IngestError::BlockNotAvailable(_) => "block_not_available",
+            IngestError::NotStreamed(_) => "not_streamed",
+            IngestError::RateLimited(_) => "rate_limited",
+            IngestError::Decode(_) => "decode",
+            IngestError::Sink(_) => "sink",
+        }
+    }
+
+    /// Whether trying the same slot again may succeed. Skipped and pruned slots get the same
+    /// answer from every endpoint, and a block that failed to decode will fail again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            IngestError::Rpc(_)
+            | IngestError::BlockNotAvailable(_)
+            | IngestError::NotStreamed(_)
+            | IngestError::RateLimited(_)
+            | IngestError::Sink(_) => true,
+            IngestError::SlotSkipped(_) | IngestError::SlotPruned(_) | IngestError::Decode(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngestError::Rpc(e) => write!(f, "RPC request failed: {}", e),
+            IngestError::SlotSkipped(slot) => write!(f, "slot {} was skipped by its leader", slot),
+            IngestError::SlotPruned(slot) => write!(f, "block {} is no longer available from the RPC", slot),
+            IngestError::BlockNotAvailable(slot) => write!(f, "block {} is not available from the RPC yet", slot),
+            IngestError::NotStreamed(slot) => write!(f, "slot {} was not streamed while the subscription was down", slot),
+            IngestError::RateLimited(e) => write!(f, "rate limited: {}", e),
+            IngestError::Decode(message) => write!(f, "failed to decode block: {}", message),
+            IngestError::Sink(e) => write!(f, "failed to write rows: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IngestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IngestError::Rpc(e) | IngestError::Sink(e) => Some(e.as_ref()),
+            IngestError::RateLimited(e) => Some(e.as_ref()),
+            IngestError::SlotSkipped(_)
+            | IngestError::SlotPruned(_)
+            | IngestError::BlockNotAvailable(_)
+            | IngestError::NotStreamed(_)
+            | IngestError::Decode(_) => None,
+        }
+    }
+}
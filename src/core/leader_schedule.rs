@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_sdk::reward_type::RewardType;
+use solana_transaction_status::Reward;
+use tokio::sync::Mutex;
+use super::rpc_pool::RpcPool;
+
+// Only the most recent epochs are kept; a backfill moves through epochs in order
+const MAX_CACHED_EPOCHS: usize = 4;
+
+/// Leader for every slot of one epoch, stored as indices into the list of leader identities
+#[derive(Debug)]
+pub struct EpochLeaders {
+    identities: Vec<String>,
+    slot_leaders: Vec<u32>,
+}
+
+impl EpochLeaders {
+    const UNKNOWN: u32 = u32::MAX;
+
+    /// Build from a `getLeaderSchedule` response (identity -> slot indices within the epoch)
+    pub fn from_schedule(schedule: &HashMap<String, Vec<usize>>, slots_in_epoch: u64) -> Self {
+        let mut identities = Vec::with_capacity(schedule.len());
+        let mut slot_leaders = vec![Self::UNKNOWN; slots_in_epoch as usize];
+        for (identity, slot_indices) in schedule {
+            let identity_idx = identities.len() as u32;
+            identities.push(identity.clone());
+            for &slot_index in slot_indices {
+                if let Some(leader) = slot_leaders.get_mut(slot_index) {
+                    *leader = identity_idx;
+                }
+            }
+        }
+        EpochLeaders { identities, slot_leaders }
+    }
+
+    pub fn leader(&self, slot_index: u64) -> Option<&str> {
+        match self.slot_leaders.get(slot_index as usize) {
+            Some(&idx) if idx != Self::UNKNOWN => Some(self.identities[idx as usize].as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// The block producer's identity, taken from the fee reward it was paid for the block
+pub fn leader_from_rewards(rewards: &[Reward]) -> Option<String> {
+    rewards
+        .iter()
+        .find(|r| r.reward_type == Some(RewardType::Fee))
+        .map(|r| r.pubkey.clone())
+}
+
+/// Caches the leader schedule per epoch so leaders can be resolved without an RPC call per block
+#[derive(Default)]
+pub struct LeaderScheduleCache {
+    epoch_schedule: Mutex<Option<EpochSchedule>>,
+    // None records an epoch whose schedule the RPC could not provide, so it is not requested again
+    epochs: Mutex<Vec<(u64, Option<Arc<EpochLeaders>>)>>,
+}
+
+impl LeaderScheduleCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn epoch_schedule(&self, rpc_pool: &RpcPool) -> Option<EpochSchedule> {
+        let mut cached = self.epoch_schedule.lock().await;
+        if cached.is_none() {
+            match rpc_pool.get_epoch_schedule().await {
+                Ok(schedule) => *cached = Some(schedule),
+                Err(e) => eprintln!("WARNING: Failed to fetch epoch schedule: {}", e),
+            }
+        }
+        *cached
+    }
+
+    async fn epoch_leaders(&self, rpc_pool: &RpcPool, epoch_schedule: &EpochSchedule, epoch: u64) -> Option<Arc<EpochLeaders>> {
+        // Holding the lock while fetching makes concurrent lookups wait for a single request
+        let mut epochs = self.epochs.lock().await;
+        if let Some((_, leaders)) = epochs.iter().find(|(e, _)| *e == epoch) {
+            return leaders.clone();
+        }
+
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+        let leaders = match rpc_pool.get_leader_schedule(first_slot).await {
+            Ok(Some(schedule)) => {
+                println!("Cached leader schedule for epoch {}", epoch);
+                Some(Arc::new(EpochLeaders::from_schedule(&schedule, epoch_schedule.get_slots_in_epoch(epoch))))
+            }
+            Ok(None) => {
+                eprintln!("WARNING: Leader schedule for epoch {} is not available from the RPC", epoch);
+                None
+            }
+            Err(e) => {
+                // Transient failure, so do not remember it
+                eprintln!("WARNING: Failed to fetch leader schedule for epoch {}: {}", epoch, e);
+                return None;
+            }
+        };
+
+        if epochs.len() >= MAX_CACHED_EPOCHS {
+            epochs.remove(0);
+        }
+        epochs.push((epoch, leaders.clone()));
+        leaders
+    }
+
+    /// Epoch containing `slot`, or `None` if the epoch schedule is unavailable
+    pub async fn get_epoch(&self, rpc_pool: &RpcPool, slot: u64) -> Option<u64> {
+        Some(self.epoch_schedule(rpc_pool).await?.get_epoch(slot))
+    }
+
+    /// Scheduled leader of `slot`, or `None` if the schedule for its epoch is unavailable
+    pub async fn get_leader(&self, rpc_pool: &RpcPool, slot: u64) -> Option<String> {
+        let epoch_schedule = self.epoch_schedule(rpc_pool).await?;
+        let (epoch, slot_index) = epoch_schedule.get_epoch_and_slot_index(slot);
+        let leaders = self.epoch_leaders(rpc_pool, &epoch_schedule, epoch).await?;
+        leaders.leader(slot_index).map(str::to_string)
+    }
+}
@@ -1,26 +1,116 @@
 #[path = "../analyzer/mod.rs"]
 mod analyzer;
 
+mod backfill;
 mod block_analyzer;
 mod block_fetcher;
+mod config;
+mod failed_slots;
+mod gap_audit;
+mod ingest_error;
+#[cfg(feature = "geyser")]
+mod geyser;
+#[cfg(feature = "geyser")]
+mod geyser_proto;
+mod leader_schedule;
+mod pipeline;
+mod replay;
+mod retry;
+mod rpc_pool;
+mod sink;
+mod slot_status;
+mod slot_subscription;
 mod transaction_parser;
 
+use std::sync::Arc;
+use clap::{Parser, Subcommand};
 use clickhouse::Client;
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::commitment_config::CommitmentConfig;
-use block_fetcher::{fetch_and_process_block, should_skip_old_slot};
+use futures::StreamExt;
+use analyzer::landing_services::init_registry;
+use analyzer::registry::AnalyzerRegistry;
+use backfill::run_backfill;
+use block_fetcher::{is_at_tip, should_skip_old_slot, store_block, BlockFetcher};
+use block_analyzer::build_registry;
+use config::{redact_password, ClickHouseConfig, Config, ConfigArgs, IngestConfig, SinkKind};
+use failed_slots::{record_failed_slot, run_retry_failed};
+use gap_audit::run_gap_audit;
+use ingest_error::IngestError;
+use pipeline::fetch_blocks_to_tip;
+use replay::{run_replay, BlockRecorder};
+use retry::RetryPolicy;
+use rpc_pool::{redact_url, RpcPool};
+use sink::{flush_periodically, ClickHouseSink, JsonlSink, ParquetSink, Sink, FLUSH_CHECK_INTERVAL};
+use slot_status::{classify_fetch_error, record_slot_status, SlotStatus};
+use slot_subscription::SlotSubscription;
 
-async fn init_clickhouse() -> Client {
-    let password = std::env::var("CLICKHOUSE_PASSWORD").unwrap_or_else(|_| "solana123".to_string());
+const BLOCKS_SCHEMA: &str = include_str!("../../clickhouse/schema_blocks.sql");
+const SLOT_STATUS_SCHEMA: &str = include_str!("../../clickhouse/schema_slot_status.sql");
+
+#[derive(Parser)]
+#[command(name = "main", about = "Solana block fetcher & analyzer")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    /// Delete all stored blocks and analysis rows before starting
+    #[arg(long, global = true)]
+    reset: bool,
+
+    /// Skip the confirmation prompt for --reset
+    #[arg(long, global = true, requires = "reset")]
+    yes: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Stream new blocks from the chain tip (default)
+    Stream,
+    /// Fetch and analyze every block in an inclusive slot range, resuming where a previous run stopped
+    Backfill {
+        #[arg(long)]
+        start_slot: u64,
+        #[arg(long)]
+        end_slot: u64,
+    },
+    /// Find blocks in an inclusive slot range that are on chain (getBlocks) but not stored, and fetch them again
+    Audit {
+        #[arg(long)]
+        start_slot: u64,
+        #[arg(long)]
+        end_slot: u64,
+        /// Only report the missing blocks
+        #[arg(long)]
+        report_only: bool,
+    },
+    /// Fetch and analyze the slots in the failed_slots table again, marking the recovered ones resolved
+    RetryFailed,
+    /// Analyze blocks saved with --record (a directory of <slot>.json files or a .jsonl archive) without any RPC
+    Replay {
+        #[arg(long)]
+        path: std::path::PathBuf,
+    },
+    /// Stream blocks with their transactions from a Yellowstone gRPC (Geyser) endpoint instead of the RPC
+    #[cfg(feature = "geyser")]
+    Geyser,
+}
+
+async fn init_clickhouse(config: &ClickHouseConfig) -> Client {
     Client::default()
-        .with_url("http://localhost:8123")
-        .with_database("solana")
-        .with_user("default")
-        .with_password(&password)
+        .with_url(&config.url)
+        .with_database(&config.database)
+        .with_user(&config.user)
+        .with_password(&config.password)
+}
+
+fn init_rpc_pool(config: &Config) -> RpcPool {
+    RpcPool::new(&config.sorted_endpoints(), config.commitment())
 }
 
-async fn execute_schema_file(client: &Client, schema_sql: &str, table_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Process SQL: remove comments, USE statements, and replace table name with solana.table_name
+async fn execute_schema_file(client: &Client, database: &str, schema_sql: &str, table_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // Process SQL: remove comments, USE statements, and replace table name with database.table_name
     let sql = schema_sql
         .lines()
         .filter(|line| {
@@ -29,9 +119,9 @@ async fn execute_schema_file(client: &Client, schema_sql: &str, table_name: &str
         })
         .map(|line| {
             let trimmed = line.trim();
-            // Replace CREATE TABLE line with solana.table_name
+            // Replace CREATE TABLE line with database.table_name
             if trimmed.starts_with("CREATE TABLE IF NOT EXISTS") {
-                format!("CREATE TABLE IF NOT EXISTS solana.{}", table_name)
+                format!("CREATE TABLE IF NOT EXISTS {}.{}", database, table_name)
             } else {
                 line.to_string()
             }
@@ -48,105 +138,285 @@ async fn execute_schema_file(client: &Client, schema_sql: &str, table_name: &str
     Ok(())
 }
 
-async fn initialize_database(client: &Client) -> Result<(), Box<dyn std::error::Error>> {
+async fn initialize_database(client: &Client, database: &str, analyzers: &AnalyzerRegistry) -> Result<(), Box<dyn std::error::Error>> {
     println!("Initializing database...");
     
     // Create database if it doesn't exist
-    client.query("CREATE DATABASE IF NOT EXISTS solana").execute().await?;
+    client.query(&format!("CREATE DATABASE IF NOT EXISTS {}", database)).execute().await?;
     
-    // Create blocks table (no schema file exists for it)
-    client.query(
-        "CREATE TABLE IF NOT EXISTS solana.blocks
-        (
-            slot UInt64,
-            parent_slot UInt64,
-            blockhash String,
-            previous_blockhash String,
-            block_time UInt64,
-            transaction_count UInt32,
-            created_at DateTime DEFAULT now()
-        )
-        ENGINE = MergeTree()
-        ORDER BY (slot)
-        PARTITION BY toYYYYMM(toDateTime(block_time))"
-    ).execute().await?;
+    execute_schema_file(client, database, BLOCKS_SCHEMA, "blocks").await?;
     println!("  Created table: blocks");
     
-    // Execute schema files
-    execute_schema_file(client, include_str!("../../clickhouse/schema_bundling.sql"), "bundling_analysis").await?;
-    println!("  Created table: bundling_analysis");
+    execute_schema_file(client, database, include_str!("../../clickhouse/schema_backfill_progress.sql"), "backfill_progress").await?;
+    println!("  Created table: backfill_progress");
     
-    execute_schema_file(client, include_str!("../../clickhouse/schema_fees.sql"), "fee_landscape").await?;
-    println!("  Created table: fee_landscape");
+    execute_schema_file(client, database, SLOT_STATUS_SCHEMA, "slot_status").await?;
+    println!("  Created table: slot_status");
     
-    execute_schema_file(client, include_str!("../../clickhouse/schema_program_fees.sql"), "program_fee_analysis").await?;
-    println!("  Created table: program_fee_analysis");
+    // Skip rates only count slots with a known outcome; unavailable slots say nothing about the leader
+    client.query(&format!(
+        "CREATE VIEW IF NOT EXISTS {db}.validator_skip_rates AS
+        SELECT
+            epoch,
+            leader,
+            countIf(status = 'produced') AS produced,
+            countIf(status = 'skipped') AS skipped,
+            countIf(status = 'unavailable') AS unavailable,
+            skipped / nullIf(produced + skipped, 0) AS skip_rate
+        FROM {db}.slot_status FINAL
+        WHERE leader IS NOT NULL AND epoch IS NOT NULL
+        GROUP BY epoch, leader",
+        db = database
+    )).execute().await?;
+    println!("  Created view: validator_skip_rates");
     
-    execute_schema_file(client, include_str!("../../clickhouse/schema_fee_by_type.sql"), "fee_by_transaction_type").await?;
-    println!("  Created table: fee_by_transaction_type");
+    execute_schema_file(client, database, include_str!("../../clickhouse/schema_failed_slots.sql"), "failed_slots").await?;
+    println!("  Created table: failed_slots");
+    
+    // Tables are created for every analyzer, so enabling one later needs no manual setup
+    for analyzer in analyzers.all() {
+        execute_schema_file(client, database, analyzer.schema(), analyzer.table()).await?;
+        println!("  Created table: {}", analyzer.table());
+    }
+    
+    migrate_schema(client, database, analyzers).await;
     
     println!("Database initialized.\n");
     Ok(())
 }
 
-async fn clear_database(client: &Client) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Clearing database...");
-    let tables = vec![
-        "blocks",
-        "bundling_analysis",
-        "program_fee_analysis",
-        "fee_landscape",
-        "fee_by_transaction_type",
-    ];
-    
-    for table in tables {
-        match client.query(&format!("TRUNCATE TABLE IF EXISTS {}", table)).execute().await {
-            Ok(_) => println!("  Cleared table: {}", table),
-            Err(e) => eprintln!("  Warning: Failed to clear table {}: {:?}", table, e),
+/// Rebuild `table` from `schema_sql` if it still uses the plain MergeTree engine, which keeps every
+/// duplicate row written when a slot is processed again. The engine of a table cannot be altered.
+async fn migrate_to_replacing_merge_tree(client: &Client, database: &str, table: &str, schema_sql: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let engine: Option<String> = client
+        .query("SELECT engine FROM system.tables WHERE database = ? AND name = ?")
+        .bind(database)
+        .bind(table)
+        .fetch_optional()
+        .await?;
+    if engine.as_deref() != Some("MergeTree") {
+        return Ok(());
+    }
+    println!("  Migrating table {} to ReplacingMergeTree...", table);
+    rebuild_table(client, database, table, schema_sql).await
+}
+
+/// Rebuild `table` from `schema_sql` if its sorting key is still `old_sorting_key`. The sorting key
+/// of a table cannot be altered, so its rows are copied into a new table that is then swapped in.
+async fn migrate_sorting_key(client: &Client, database: &str, table: &str, old_sorting_key: &str, schema_sql: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let sorting_key: Option<String> = client
+        .query("SELECT sorting_key FROM system.tables WHERE database = ? AND name = ?")
+        .bind(database)
+        .bind(table)
+        .fetch_optional()
+        .await?;
+    if sorting_key.as_deref() != Some(old_sorting_key) {
+        return Ok(());
+    }
+    println!("  Migrating table {} to its new sorting key...", table);
+    rebuild_table(client, database, table, schema_sql).await
+}
+
+/// Rebuild `table` from `schema_sql` if `column` still has type `old_type`. Key and version columns
+/// cannot be altered, so its rows are copied into a new table that is then swapped in.
+async fn migrate_column_type(client: &Client, database: &str, table: &str, column: &str, old_type: &str, schema_sql: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let column_type: Option<String> = client
+        .query("SELECT type FROM system.columns WHERE database = ? AND table = ? AND name = ?")
+        .bind(database)
+        .bind(table)
+        .bind(column)
+        .fetch_optional()
+        .await?;
+    if column_type.as_deref() != Some(old_type) {
+        return Ok(());
+    }
+    println!("  Migrating column {} of table {} to its new type...", column, table);
+    rebuild_table(client, database, table, schema_sql).await
+}
+
+/// Copy the rows of `table` into a new table created from `schema_sql` and swap it in
+async fn rebuild_table(client: &Client, database: &str, table: &str, schema_sql: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // A leftover from an interrupted migration is rebuilt from scratch
+    let staging = format!("{}_replacing", table);
+    client.query(&format!("DROP TABLE IF EXISTS {}.{}", database, staging)).execute().await?;
+    execute_schema_file(client, database, schema_sql, &staging).await?;
+
+    // Columns the old table lacks, such as ingest_version, take their defaults
+    let columns: Vec<String> = client
+        .query("SELECT name FROM system.columns WHERE database = ? AND table = ? AND name IN (SELECT name FROM system.columns WHERE database = ? AND table = ?) ORDER BY position")
+        .bind(database)
+        .bind(staging.as_str())
+        .bind(database)
+        .bind(table)
+        .fetch_all()
+        .await?;
+    let columns = columns.join(", ");
+    client
+        .query(&format!("INSERT INTO {db}.{staging} ({columns}) SELECT {columns} FROM {db}.{table}", db = database))
+        .execute()
+        .await?;
+    client.query(&format!("EXCHANGE TABLES {db}.{table} AND {db}.{staging}", db = database)).execute().await?;
+    client.query(&format!("DROP TABLE {}.{}", database, staging)).execute().await?;
+    Ok(())
+}
+
+/// Delete transactions `retention_days` after they were stored, or keep them forever. The TTL is
+/// based on when a row was written rather than on block_time, so backfilled blocks are not deleted
+/// as soon as they arrive.
+async fn apply_transactions_retention(client: &Client, database: &str, retention_days: Option<u32>) -> Result<(), Box<dyn std::error::Error>> {
+    let create_query: Option<String> = client
+        .query("SELECT create_table_query FROM system.tables WHERE database = ? AND name = 'transactions'")
+        .bind(database)
+        .fetch_optional()
+        .await?;
+    let has_ttl = create_query.is_some_and(|query| query.contains(" TTL "));
+    match retention_days {
+        Some(days) => {
+            client
+                .query(&format!("ALTER TABLE {}.transactions MODIFY TTL created_at + INTERVAL {} DAY", database, days))
+                .execute()
+                .await?;
+            println!("  Transactions are kept for {} days", days);
+        }
+        None if has_ttl => {
+            client.query(&format!("ALTER TABLE {}.transactions REMOVE TTL", database)).execute().await?;
+            println!("  Removed transactions TTL, transactions are kept until deleted");
         }
+        None => {}
     }
-    
+    Ok(())
+}
+
+async fn migrate_schema(client: &Client, database: &str, analyzers: &AnalyzerRegistry) {
     // Drop old/removed tables if they exist
     let drop_queries = vec![
-        "DROP TABLE IF EXISTS solana.landing_method_analysis",
-        "DROP TABLE IF EXISTS solana.transaction_age_analysis",
+        format!("DROP TABLE IF EXISTS {}.landing_method_analysis", database),
+        format!("DROP TABLE IF EXISTS {}.transaction_age_analysis", database),
     ];
     
     for query in drop_queries {
-        let _ = client.query(query).execute().await;
+        let _ = client.query(&query).execute().await;
     }
     
     // Ensure bundling_analysis table has the correct columns (for migration from old schemas)
     let alter_queries = vec![
-        "ALTER TABLE solana.bundling_analysis ADD COLUMN IF NOT EXISTS landing_service String DEFAULT ''",
-        "ALTER TABLE solana.bundling_analysis ADD COLUMN IF NOT EXISTS landing_service_count UInt32 DEFAULT 0",
-        "ALTER TABLE solana.bundling_analysis ADD COLUMN IF NOT EXISTS largest_bundle_size UInt32 DEFAULT 0",
+        format!("ALTER TABLE {}.bundling_analysis ADD COLUMN IF NOT EXISTS landing_service String DEFAULT ''", database),
+        format!("ALTER TABLE {}.bundling_analysis ADD COLUMN IF NOT EXISTS landing_service_count UInt32 DEFAULT 0", database),
+        format!("ALTER TABLE {}.bundling_analysis ADD COLUMN IF NOT EXISTS largest_bundle_size UInt32 DEFAULT 0", database),
+        format!("ALTER TABLE {}.bundling_analysis ADD COLUMN IF NOT EXISTS cpi_tip_count UInt32 DEFAULT 0", database),
     ];
     
     for query in alter_queries {
-        let _ = client.query(query).execute().await;
+        let _ = client.query(&query).execute().await;
     }
     
+    // Unresolved leaders are stored as NULL rather than an empty string
+    let _ = client.query(&format!("ALTER TABLE {}.bundling_analysis MODIFY COLUMN validator_key Nullable(String)", database)).execute().await;
+    
     // Remove fee_ordering_correlation column from fee_landscape if it exists
-    let _ = client.query("ALTER TABLE solana.fee_landscape DROP COLUMN IF EXISTS fee_ordering_correlation").execute().await;
+    let _ = client.query(&format!("ALTER TABLE {}.fee_landscape DROP COLUMN IF EXISTS fee_ordering_correlation", database)).execute().await;
+    
+    // Compute unit aggregates added to fee_landscape
+    let fee_landscape_columns = vec![
+        "total_compute_units UInt64 DEFAULT 0",
+        "compute_units_p50 UInt64 DEFAULT 0",
+        "compute_units_p90 UInt64 DEFAULT 0",
+        "compute_units_p99 UInt64 DEFAULT 0",
+        "block_cu_utilization_percent Float32 DEFAULT 0",
+        // Fee breakdown into base fee, priority fee and tip
+        "total_base_fee UInt64 DEFAULT 0",
+        "total_priority_fee UInt64 DEFAULT 0",
+        "total_tip UInt64 DEFAULT 0",
+        "compute_unit_price_p50 UInt64 DEFAULT 0",
+        "compute_unit_price_p90 UInt64 DEFAULT 0",
+        "compute_unit_limit_avg Float64 DEFAULT 0",
+    ];
+    for column in fee_landscape_columns {
+        let _ = client.query(&format!("ALTER TABLE {}.fee_landscape ADD COLUMN IF NOT EXISTS {}", database, column)).execute().await;
+    }
+    
+    let program_fee_columns = vec![
+        "total_base_fee UInt64 DEFAULT 0",
+        "total_priority_fee UInt64 DEFAULT 0",
+        "total_tip UInt64 DEFAULT 0",
+        "compute_unit_price_avg Float64 DEFAULT 0",
+        "compute_unit_limit_avg Float64 DEFAULT 0",
+    ];
+    for column in program_fee_columns {
+        let _ = client.query(&format!("ALTER TABLE {}.program_fee_analysis ADD COLUMN IF NOT EXISTS {}", database, column)).execute().await;
+    }
+    // Rows written before program_id existed only know the program by name
+    let _ = client.query(&format!("ALTER TABLE {}.program_fee_analysis ADD COLUMN IF NOT EXISTS program_id String DEFAULT program_name AFTER program_type", database)).execute().await;
+    
+    // Slot keyed tables deduplicate rows, so processing a slot again does not inflate the totals
+    let mut tables = vec![("blocks", BLOCKS_SCHEMA)];
+    tables.extend(analyzers.all().map(|analyzer| (analyzer.table(), analyzer.schema())));
+    for (table, schema_sql) in tables {
+        if let Err(e) = migrate_to_replacing_merge_tree(client, database, table, schema_sql).await {
+            eprintln!("  Warning: Failed to migrate table {} to ReplacingMergeTree: {:?}", table, e);
+        }
+    }
+
+    // Tip account candidates keep one row per recipient, not one per tracker restart
+    if let Some(analyzer) = analyzers.all().find(|analyzer| analyzer.table() == "tip_account_candidates") {
+        if let Err(e) = migrate_sorting_key(client, database, analyzer.table(), "recipient, first_seen_slot", analyzer.schema()).await {
+            eprintln!("  Warning: Failed to migrate table {}: {:?}", analyzer.table(), e);
+        }
+    }
+
+    // A slot whose outcome changes within a second keeps the later row only with a sub-second version
+    if let Err(e) = migrate_column_type(client, database, "slot_status", "updated_at", "DateTime", SLOT_STATUS_SCHEMA).await {
+        eprintln!("  Warning: Failed to migrate table slot_status: {:?}", e);
+    }
+}
+
+/// Rebuild the state of analyzers that keep it across blocks from the rows they stored before a restart
+async fn restore_analyzers(sink: &dyn Sink, analyzers: &AnalyzerRegistry) {
+    for analyzer in analyzers.enabled().filter(|analyzer| analyzer.is_stateful()) {
+        let restored = match sink.read_rows(analyzer.table()).await {
+            Ok(rows) => analyzer.restore(rows.as_ref()).map(|_| rows.len()).map_err(|e| e.into()),
+            Err(e) => Err(e),
+        };
+        match restored {
+            Ok(count) => println!("Restored analyzer {} from {} stored rows", analyzer.name(), count),
+            Err(e) => eprintln!("WARNING: Failed to restore analyzer {}, it starts from scratch: {}", analyzer.name(), e),
+        }
+    }
+}
+
+async fn clear_database(client: &Client, analyzers: &AnalyzerRegistry) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Clearing database...");
+    let mut tables = vec!["blocks", "backfill_progress", "slot_status", "failed_slots"];
+    tables.extend(analyzers.all().map(|analyzer| analyzer.table()));
+    
+    for table in tables {
+        match client.query(&format!("TRUNCATE TABLE IF EXISTS {}", table)).execute().await {
+            Ok(_) => println!("  Cleared table: {}", table),
+            Err(e) => eprintln!("  Warning: Failed to clear table {}: {:?}", table, e),
+        }
+    }
     
     println!("Database cleared.\n");
     Ok(())
 }
 
-async fn get_last_processed_slot(client: &Client) -> u64 {
-    client
-        .query("SELECT slot FROM blocks ORDER BY slot DESC LIMIT 1")
-        .fetch_one()
-        .await
-        .unwrap_or(0)
+fn confirm_reset() -> bool {
+    println!("WARNING: --reset will permanently delete all stored blocks and analysis rows.");
+    print!("Type 'yes' to continue: ");
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    answer.trim().eq_ignore_ascii_case("yes")
 }
 
-async fn initialize_from_current_slot(rpc_client: &RpcClient) -> u64 {
-    match rpc_client.get_slot() {
+async fn initialize_from_current_slot(rpc_pool: &RpcPool) -> u64 {
+    match rpc_pool.get_slot().await {
         Ok(current_slot) => {
             let start_slot = current_slot.saturating_sub(5);
-            println!("Database empty, starting from slot {}", start_slot);
+            println!("No stored blocks, starting from slot {}", start_slot);
             start_slot
         }
         Err(e) => {
@@ -158,104 +428,311 @@ async fn initialize_from_current_slot(rpc_client: &RpcClient) -> u64 {
     }
 }
 
-async fn get_current_slot(rpc_client: &RpcClient) -> Result<u64, Box<dyn std::error::Error>> {
-    rpc_client.get_slot().map_err(|e| e.into())
+async fn get_current_slot(rpc_pool: &RpcPool) -> Result<u64, Box<dyn std::error::Error>> {
+    rpc_pool.get_slot().await.map_err(|e| e.into())
+}
+
+fn log_endpoint_health(rpc_pool: &RpcPool) {
+    let now = std::time::Instant::now();
+    for (url, health) in rpc_pool.health() {
+        eprintln!(
+            "  {}: {} requests, {} failures, {:.0}ms average latency{}",
+            redact_url(&url),
+            health.requests,
+            health.failures,
+            health.latency_ms,
+            if health.is_backing_off(now) { ", backing off" } else { "" }
+        );
+    }
 }
 
 async fn process_slots(
-    client: &Client,
-    rpc_client: &RpcClient,
+    sink: &dyn Sink,
+    fetcher: &Arc<BlockFetcher>,
     start_slot: u64,
     current_slot: u64,
     last_processed_slot: &mut u64,
-    alchemy_key: &str,
+    ingest: &IngestConfig,
+    analyzers: &AnalyzerRegistry,
 ) {
-    let end_slot = current_slot.min(start_slot + 10);
+    let end_slot = current_slot.min(start_slot + ingest.batch_size - 1);
 
-    for slot in start_slot..=end_slot {
-        match fetch_and_process_block(client, rpc_client, slot, alchemy_key).await {
+    let mut blocks = Box::pin(fetch_blocks_to_tip(Arc::clone(fetcher), start_slot..=end_slot, current_slot, ingest.concurrency));
+    while let Some((slot, fetched)) = blocks.next().await {
+        let (status, result) = match fetched {
+            Ok(block) => (SlotStatus::Produced, store_block(sink, &block, analyzers, fetcher.retry_policy()).await),
+            Err(e) => (classify_fetch_error(&e), Err(e)),
+        };
+        match result {
             Ok(_) => {
                 println!("Block {} fetched and analyzed", slot);
+                record_slot_status(sink, fetcher, slot, status, String::new()).await;
                 *last_processed_slot = slot;
             }
             Err(e) => {
-                let err_str = format!("{:?}", e);
-                if should_skip_old_slot(&err_str, slot, current_slot) {
+                if should_skip_old_slot(&e, slot, current_slot) {
+                    record_slot_status(sink, fetcher, slot, status, e.to_string()).await;
+                    record_failed_slot(sink, slot, &e, fetcher.retry_policy().attempts_for(&e)).await;
+                    let resume_slot = current_slot.saturating_sub(10);
                     println!("Skipping old slot {} (not available), jumping to recent slots", slot);
-                    *last_processed_slot = current_slot.saturating_sub(10);
+                    println!("Run `audit --start-slot {} --end-slot {}` to recover any blocks in between", slot, resume_slot);
+                    *last_processed_slot = resume_slot;
+                    break;
+                } else if matches!(e, IngestError::BlockNotAvailable(_)) && is_at_tip(slot, current_slot) {
+                    // The RPC does not have the block yet, so it and the slots after it are fetched on the next poll
                     break;
-                } else if slot == current_slot {
+                } else if slot == current_slot && e.is_retryable() {
+                    // The tip block may not be available yet, so it is retried on the next pass
                     break;
                 } else {
-                    eprintln!("WARNING: Failed to process block {}: {}", slot, e);
+                    if status == SlotStatus::Skipped {
+                        println!("Slot {} was skipped by its leader", slot);
+                    } else {
+                        eprintln!("WARNING: Failed to process block {}: {}", slot, e);
+                        record_failed_slot(sink, slot, &e, fetcher.retry_policy().attempts_for(&e)).await;
+                    }
+                    record_slot_status(sink, fetcher, slot, status, e.to_string()).await;
                 }
             }
         }
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     }
 }
 
-#[tokio::main]
-async fn main() {
-    println!("\n=== Welcome to Solana Block Analyzer ===\n");
-    
-    // Hardcoded API keys
-    let getblock_token = "1f95ca0bb31f442f8a598bd1d4043da9";
-    let alchemy_key = "AFjoSzKjqv6Eq53OsF2xe";
-    
-    // Display ClickHouse credentials
-    let clickhouse_password = std::env::var("CLICKHOUSE_PASSWORD").unwrap_or_else(|_| "solana123".to_string());
-    println!("\n=== ClickHouse Configuration ===");
-    println!("Username: default");
-    println!("Password: {}", clickhouse_password);
-    println!("Database: solana");
-    println!("Dashboard: http://localhost:8123/play");
-    println!("===============================\n");
-    
-    let client = init_clickhouse().await;
-    
-    // Initialize database (create tables if they don't exist)
-    if let Err(e) = initialize_database(&client).await {
-        eprintln!("Error: Failed to initialize database: {:?}", e);
-        eprintln!("Please ensure ClickHouse is running and accessible.");
-        return;
-    }
-    
-    // Clear database on each run
-    if let Err(e) = clear_database(&client).await {
-        eprintln!("Warning: Failed to clear database: {:?}", e);
-        eprintln!("Continuing anyway...\n");
+/// Wait for the chain tip to move. A connected subscription is waited on for up to the poll interval;
+/// without one (or when it is not authoritative) the tip is read from the RPC.
+async fn next_current_slot(rpc_pool: &RpcPool, subscription: Option<&mut SlotSubscription>, ingest: &IngestConfig) -> Result<u64, Box<dyn std::error::Error>> {
+    if let Some(subscription) = subscription.filter(|s| s.is_connected()) {
+        let timeout = tokio::time::Duration::from_millis(ingest.poll_interval_ms);
+        if let Some(slot) = subscription.wait_for_slot(timeout).await {
+            if subscription.is_authoritative() {
+                return Ok(slot);
+            }
+        }
     }
-    
-    // Initialize RPC client with user's GetBlock token
-    let rpc_url = format!("https://go.getblock.us/{}", getblock_token);
-    let rpc_client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::finalized());
+    get_current_slot(rpc_pool).await
+}
 
+async fn run_stream(
+    sink: &dyn Sink,
+    fetcher: Arc<BlockFetcher>,
+    mut subscription: Option<SlotSubscription>,
+    ingest: &IngestConfig,
+    analyzers: &AnalyzerRegistry,
+) {
     println!("Starting Solana block fetcher & analyzer - streaming mode");
     println!("Monitoring for new blocks...\n");
 
-    let mut last_processed_slot = get_last_processed_slot(&client).await;
+    let mut last_processed_slot = sink.last_block_slot().await.unwrap_or(0);
 
     if last_processed_slot == 0 {
-        last_processed_slot = initialize_from_current_slot(&rpc_client).await;
+        last_processed_slot = initialize_from_current_slot(fetcher.rpc_pool()).await;
         if last_processed_slot == 0 {
             return;
         }
+    } else {
+        println!("Resuming after last processed slot {}", last_processed_slot);
     }
 
     loop {
-        match get_current_slot(&rpc_client).await {
+        match next_current_slot(fetcher.rpc_pool(), subscription.as_mut(), ingest).await {
             Ok(current_slot) => {
                 if current_slot > last_processed_slot {
-                    process_slots(&client, &rpc_client, last_processed_slot + 1, current_slot, &mut last_processed_slot, &alchemy_key).await;
-                } else {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                    process_slots(sink, &fetcher, last_processed_slot + 1, current_slot, &mut last_processed_slot, ingest, analyzers).await;
+                } else if !subscription.as_ref().is_some_and(|s| s.is_connected()) {
+                    // A connected subscription already waited for the tip to move
+                    tokio::time::sleep(tokio::time::Duration::from_millis(ingest.poll_interval_ms)).await;
                 }
             }
             Err(e) => {
                 eprintln!("WARNING: Failed to get slot: {:?}", e);
+                log_endpoint_health(fetcher.rpc_pool());
                 tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
             }
         }
     }
 }
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    let config = match cli.config.resolve() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+
+    println!("\n=== Welcome to Solana Block Analyzer ===\n");
+    
+    if config.sink() == SinkKind::ClickHouse {
+        // Display ClickHouse credentials
+        println!("\n=== ClickHouse Configuration ===");
+        println!("URL: {}", config.clickhouse.url);
+        println!("Username: {}", config.clickhouse.user);
+        println!("Password: {}", redact_password(&config.clickhouse.password));
+        println!("Database: {}", config.clickhouse.database);
+        println!("Dashboard: {}/play", config.clickhouse.url.trim_end_matches('/'));
+        println!("===============================\n");
+    } else {
+        println!("\n=== Output ===");
+        println!("Sink: {}", config.output.sink);
+        println!("Directory: {}", config.output.directory.display());
+        println!("===============================\n");
+    }
+    
+    println!("=== RPC Endpoints ===");
+    for endpoint in config.sorted_endpoints() {
+        println!("Priority {}: {}", endpoint.priority, redact_url(&endpoint.url));
+    }
+    println!("=====================\n");
+    
+    // Tip accounts are loaded once, before any block is analyzed
+    let landing_services = match init_registry(config.analysis.landing_services_file.as_deref()) {
+        Ok(registry) => registry,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+    println!(
+        "=== Landing Services ({} tip accounts, registry version {}) ===",
+        landing_services.account_count(),
+        landing_services.version()
+    );
+    for (service, accounts) in landing_services.services() {
+        println!("{}: {}", service, accounts);
+    }
+    println!("=====================\n");
+    
+    let analyzers = match build_registry(&config.analysis) {
+        Ok(analyzers) => analyzers,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return;
+        }
+    };
+    println!("=== Analyzers ===");
+    for analyzer in analyzers.enabled() {
+        println!("{} -> {}", analyzer.name(), analyzer.table());
+    }
+    println!("=====================\n");
+    
+    let sink: Arc<dyn Sink> = match config.sink() {
+        SinkKind::ClickHouse => {
+            let client = init_clickhouse(&config.clickhouse).await;
+            
+            // Initialize database (create tables if they don't exist)
+            if let Err(e) = initialize_database(&client, &config.clickhouse.database, &analyzers).await {
+                eprintln!("Error: Failed to initialize database: {:?}", e);
+                eprintln!("Please ensure ClickHouse is running and accessible.");
+                return;
+            }
+            if let Err(e) = apply_transactions_retention(&client, &config.clickhouse.database, config.analysis.transactions_retention_days).await {
+                eprintln!("WARNING: Failed to apply transactions retention: {:?}", e);
+            }
+            
+            // Existing data is kept unless a reset is explicitly requested
+            if cli.reset {
+                if !cli.yes && !confirm_reset() {
+                    println!("Reset cancelled, exiting.");
+                    return;
+                }
+                if let Err(e) = clear_database(&client, &analyzers).await {
+                    eprintln!("Error: Failed to clear database: {:?}", e);
+                    return;
+                }
+            }
+            let sink = ClickHouseSink::new(client);
+            restore_analyzers(&sink, &analyzers).await;
+            Arc::new(sink)
+        }
+        kind => {
+            if cli.reset {
+                eprintln!("Error: --reset only applies to the clickhouse sink; delete {} instead", config.output.directory.display());
+                return;
+            }
+            let opened = if kind == SinkKind::Parquet {
+                ParquetSink::new(&config.output.directory).map(|sink| Arc::new(sink) as Arc<dyn Sink>)
+            } else {
+                JsonlSink::new(&config.output.directory).map(|sink| Arc::new(sink) as Arc<dyn Sink>)
+            };
+            match opened {
+                Ok(sink) => sink,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return;
+                }
+            }
+        }
+    };
+    
+    // Initialize the RPC endpoint pool
+    let rpc_pool = Arc::new(init_rpc_pool(&config));
+    let mut fetcher = BlockFetcher::new(rpc_pool, config.encoding()).with_retry_policy(RetryPolicy::new(&config.retry));
+    if let Some(ref path) = config.ingest.record {
+        match BlockRecorder::open(path) {
+            Ok(recorder) => {
+                println!("Recording fetched blocks to {}\n", path.display());
+                fetcher = fetcher.with_recorder(recorder);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return;
+            }
+        }
+    }
+    let fetcher = Arc::new(fetcher);
+
+    // Buffered rows are written once due even while no new rows arrive, and on Ctrl-C
+    let flusher = tokio::spawn(flush_periodically(Arc::clone(&sink), FLUSH_CHECK_INTERVAL));
+    let command = async {
+        match cli.command.unwrap_or(Command::Stream) {
+            Command::Stream => {
+                // Falls back to polling whenever the socket is down
+                let subscription = match (config.subscription(), config.websocket_url()) {
+                    (Some(kind), Some(url)) => Some(SlotSubscription::spawn(&url, kind, config.commitment())),
+                    _ => None,
+                };
+                run_stream(sink.as_ref(), fetcher, subscription, &config.ingest, &analyzers).await
+            }
+            Command::Backfill { start_slot, end_slot } => {
+                if let Err(e) = run_backfill(sink.as_ref(), fetcher, start_slot, end_slot, config.ingest.concurrency, &analyzers).await {
+                    eprintln!("Error: Backfill failed: {}", e);
+                }
+            }
+            Command::Audit { start_slot, end_slot, report_only } => {
+                if let Err(e) = run_gap_audit(sink.as_ref(), fetcher, start_slot, end_slot, config.ingest.concurrency, &analyzers, report_only).await {
+                    eprintln!("Error: Audit failed: {}", e);
+                }
+            }
+            Command::RetryFailed => {
+                if let Err(e) = run_retry_failed(sink.as_ref(), fetcher, config.ingest.concurrency, &analyzers).await {
+                    eprintln!("Error: Retrying failed slots failed: {}", e);
+                }
+            }
+            Command::Replay { path } => {
+                if let Err(e) = run_replay(sink.as_ref(), &path, &analyzers, fetcher.retry_policy()).await {
+                    eprintln!("Error: Replay failed: {}", e);
+                }
+            }
+            #[cfg(feature = "geyser")]
+            Command::Geyser => match config.geyser.endpoint {
+                Some(ref endpoint) => {
+                    let x_token = config.geyser.x_token.as_deref();
+                    geyser::run_geyser_stream(sink.as_ref(), endpoint, x_token, config.commitment(), &analyzers, &fetcher).await
+                }
+                None => eprintln!("Error: geyser.endpoint (--geyser-endpoint) must be set to stream from Geyser"),
+            },
+        }
+    };
+    tokio::select! {
+        _ = command => {}
+        _ = tokio::signal::ctrl_c() => println!("\nInterrupted, writing buffered rows..."),
+    }
+    flusher.abort();
+    if let Err(e) = sink.flush().await {
+        eprintln!("Error: Failed to write buffered rows: {}", e);
+    }
+}
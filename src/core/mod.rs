@@ -1,4 +1,21 @@
+pub mod backfill;
 pub mod block_analyzer;
 pub mod block_fetcher;
+pub mod config;
+pub mod failed_slots;
+pub mod gap_audit;
+pub mod ingest_error;
+#[cfg(feature = "geyser")]
+pub mod geyser;
+#[cfg(feature = "geyser")]
+pub mod geyser_proto;
+pub mod leader_schedule;
+pub mod pipeline;
+pub mod replay;
+pub mod retry;
+pub mod rpc_pool;
+pub mod sink;
+pub mod slot_status;
+pub mod slot_subscription;
 pub mod transaction_parser;
 
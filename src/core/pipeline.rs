@@ -0,0 +1,79 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use futures::stream::{self, Stream, StreamExt};
+use tokio::task::{JoinError, JoinHandle};
+use super::block_fetcher::{is_at_tip, BlockFetcher, FetchedBlock};
+use super::ingest_error::IngestError;
+
+/// A spawned fetch that is aborted when dropped, so fetches still in flight stop once the
+/// stream that owns them is dropped
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Future for AbortOnDrop<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Fetch and parse `slots` with up to `concurrency` blocks in flight at once.
+///
+/// Each slot is fetched on its own task so requests keep progressing while the caller
+/// analyzes and stores earlier blocks. Results are yielded in the order of `slots`
+/// regardless of which request completes first, so the caller can commit them in slot order.
+/// Dropping the stream aborts the fetches that are still in flight.
+pub fn fetch_blocks(
+    fetcher: Arc<BlockFetcher>,
+    slots: impl IntoIterator<Item = u64>,
+    concurrency: usize,
+) -> impl Stream<Item = (u64, Result<FetchedBlock, IngestError>)> {
+    fetch_blocks_with_tip(fetcher, slots, None, concurrency)
+}
+
+/// `fetch_blocks` for slots up to the chain tip `current_slot`. Blocks at the tip that the RPC
+/// does not have yet are yielded as `BlockNotAvailable` without being retried.
+pub fn fetch_blocks_to_tip(
+    fetcher: Arc<BlockFetcher>,
+    slots: impl IntoIterator<Item = u64>,
+    current_slot: u64,
+    concurrency: usize,
+) -> impl Stream<Item = (u64, Result<FetchedBlock, IngestError>)> {
+    fetch_blocks_with_tip(fetcher, slots, Some(current_slot), concurrency)
+}
+
+fn fetch_blocks_with_tip(
+    fetcher: Arc<BlockFetcher>,
+    slots: impl IntoIterator<Item = u64>,
+    current_slot: Option<u64>,
+    concurrency: usize,
+) -> impl Stream<Item = (u64, Result<FetchedBlock, IngestError>)> {
+    stream::iter(slots)
+        .map(move |slot| {
+            let fetcher = Arc::clone(&fetcher);
+            let at_tip = current_slot.is_some_and(|current_slot| is_at_tip(slot, current_slot));
+            let handle = AbortOnDrop(tokio::spawn(async move {
+                if at_tip {
+                    fetcher.fetch_tip_block(slot).await
+                } else {
+                    fetcher.fetch_block(slot).await
+                }
+            }));
+            async move {
+                let result = match handle.await {
+                    Ok(result) => result,
+                    Err(e) => Err(IngestError::Rpc(format!("Fetch task for slot {} failed: {}", slot, e).into())),
+                };
+                (slot, result)
+            }
+        })
+        .buffered(concurrency.max(1))
+}
@@ -0,0 +1,219 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_transaction_status::UiConfirmedBlock;
+use crate::analyzer::registry::AnalyzerRegistry;
+use super::block_fetcher::{parse_block, store_block};
+use super::leader_schedule::leader_from_rewards;
+use super::retry::RetryPolicy;
+use super::sink::Sink;
+
+// The slot was already read while indexing the archive
+#[derive(Deserialize)]
+struct ArchivedBlock {
+    block: UiConfirmedBlock,
+}
+
+/// One line of a JSONL block archive
+#[derive(Serialize)]
+struct ArchivedBlockRef<'a> {
+    slot: u64,
+    block: &'a UiConfirmedBlock,
+}
+
+#[derive(Deserialize)]
+struct ArchivedSlot {
+    slot: u64,
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "jsonl")
+}
+
+/// Saves `getBlock` responses as `<directory>/<slot>.json`, or as lines of a `.jsonl` archive
+pub enum BlockRecorder {
+    Directory(PathBuf),
+    Archive(Mutex<BufWriter<File>>),
+}
+
+impl BlockRecorder {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if is_archive(path) {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open block archive {}: {}", path.display(), e))?;
+            Ok(BlockRecorder::Archive(Mutex::new(BufWriter::new(file))))
+        } else {
+            std::fs::create_dir_all(path).map_err(|e| format!("Failed to create record directory {}: {}", path.display(), e))?;
+            Ok(BlockRecorder::Directory(path.to_path_buf()))
+        }
+    }
+
+    pub fn record(&self, slot: u64, block: &UiConfirmedBlock) -> Result<(), String> {
+        match self {
+            BlockRecorder::Directory(directory) => {
+                let path = directory.join(format!("{}.json", slot));
+                let contents = serde_json::to_vec(block).map_err(|e| e.to_string())?;
+                std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+            }
+            BlockRecorder::Archive(writer) => {
+                // Blocks are fetched concurrently, so lines are not necessarily in slot order
+                let line = serde_json::to_string(&ArchivedBlockRef { slot, block }).map_err(|e| e.to_string())?;
+                let mut writer = writer.lock().unwrap();
+                writeln!(writer, "{}", line).and_then(|_| writer.flush()).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+/// Parse a recorded `getBlock` response, either the bare block or a full JSON-RPC response
+pub fn parse_recorded_block(contents: &str) -> Result<UiConfirmedBlock, String> {
+    let mut value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    if let Some(result) = value.get_mut("result") {
+        value = result.take();
+    }
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+enum RecordingEntry {
+    File(PathBuf),
+    Line(u64),  // Byte offset of the line in the archive
+}
+
+/// Blocks recorded on disk, in slot order
+pub struct Recording {
+    path: PathBuf,
+    entries: Vec<(u64, RecordingEntry)>,
+}
+
+impl Recording {
+    /// Index a directory of `<slot>.json` files or a `.jsonl` archive. Blocks are loaded one at a time.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let mut entries = if is_archive(path) {
+            Self::index_archive(path)?
+        } else {
+            Self::index_directory(path)?
+        };
+        entries.sort_by_key(|(slot, _)| *slot);
+        if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!("Slot {} is recorded more than once in {}", pair[0].0, path.display()));
+        }
+        Ok(Recording { path: path.to_path_buf(), entries })
+    }
+
+    fn index_directory(path: &Path) -> Result<Vec<(u64, RecordingEntry)>, String> {
+        let read_dir = std::fs::read_dir(path).map_err(|e| format!("Failed to read recording {}: {}", path.display(), e))?;
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let file = entry.map_err(|e| e.to_string())?.path();
+            if file.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            // Only <slot>.json files are blocks
+            match file.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse::<u64>().ok()) {
+                Some(slot) => entries.push((slot, RecordingEntry::File(file))),
+                None => eprintln!("WARNING: Ignoring {}, recorded blocks are named <slot>.json", file.display()),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn index_archive(path: &Path) -> Result<Vec<(u64, RecordingEntry)>, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open block archive {}: {}", path.display(), e))?;
+        let mut reader = BufReader::new(file);
+        let mut entries = Vec::new();
+        let mut offset = 0u64;
+        let mut line = String::new();
+        let mut line_number = 0;
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+            if read == 0 {
+                break;
+            }
+            line_number += 1;
+            if !line.trim().is_empty() {
+                let archived: ArchivedSlot = serde_json::from_str(&line)
+                    .map_err(|e| format!("Invalid line {} in {}: {}", line_number, path.display(), e))?;
+                entries.push((archived.slot, RecordingEntry::Line(offset)));
+            }
+            offset += read as u64;
+        }
+        Ok(entries)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn slots(&self) -> Vec<u64> {
+        self.entries.iter().map(|(slot, _)| *slot).collect()
+    }
+
+    /// Load the `index`th block in slot order
+    pub fn load(&self, index: usize) -> Result<UiConfirmedBlock, String> {
+        let (slot, entry) = &self.entries[index];
+        match entry {
+            RecordingEntry::File(path) => {
+                let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                parse_recorded_block(&contents).map_err(|e| format!("Invalid block in {}: {}", path.display(), e))
+            }
+            RecordingEntry::Line(offset) => {
+                let mut file = File::open(&self.path).map_err(|e| e.to_string())?;
+                file.seek(SeekFrom::Start(*offset)).map_err(|e| e.to_string())?;
+                let mut line = String::new();
+                BufReader::new(file).read_line(&mut line).map_err(|e| e.to_string())?;
+                let archived: ArchivedBlock = serde_json::from_str(&line)
+                    .map_err(|e| format!("Invalid block for slot {} in {}: {}", slot, self.path.display(), e))?;
+                Ok(archived.block)
+            }
+        }
+    }
+}
+
+/// Run every recorded block through the parser and the analyzers, in slot order.
+/// Leaders are taken from the block rewards since there is no RPC to ask for the schedule.
+pub async fn run_replay(sink: &dyn Sink, path: &Path, analyzers: &AnalyzerRegistry, retry_policy: &RetryPolicy) -> Result<(), Box<dyn std::error::Error>> {
+    let recording = Recording::open(path)?;
+    if recording.is_empty() {
+        return Err(format!("No recorded blocks found in {}", path.display()).into());
+    }
+    println!("Replaying {} recorded blocks from {}", recording.len(), path.display());
+
+    let mut failed = 0u64;
+    for (index, slot) in recording.slots().into_iter().enumerate() {
+        let result: Result<(), Box<dyn std::error::Error>> = match recording.load(index) {
+            Ok(block) => {
+                let validator_key = block.rewards.as_deref().and_then(leader_from_rewards);
+                match parse_block(slot, &block, validator_key) {
+                    Ok(fetched) => store_block(sink, &fetched, analyzers, retry_policy).await.map_err(|e| e.into()),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(_) => println!("Block {} replayed", slot),
+            Err(e) => {
+                eprintln!("WARNING: Failed to replay block {}: {}", slot, e);
+                failed += 1;
+            }
+        }
+    }
+
+    sink.flush().await.map_err(|e| e as Box<dyn std::error::Error>)?;
+    println!("Replay of {} blocks complete ({} blocks failed)", recording.len(), failed);
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::future::Future;
+use std::time::Duration;
+use rand::Rng;
+use super::config::RetryConfig;
+use super::ingest_error::IngestError;
+
+/// How often and how patiently a failed slot is attempted again
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    rate_limit_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(&RetryConfig::default())
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(config: &RetryConfig) -> Self {
+        RetryPolicy {
+            max_attempts: config.max_attempts.max(1),
+            base_delay: Duration::from_millis(config.base_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+            rate_limit_delay: Duration::from_millis(config.rate_limit_delay_ms),
+        }
+    }
+
+    /// Number of attempts made for a slot whose last attempt failed with `err`
+    pub fn attempts_for(&self, err: &IngestError) -> u32 {
+        if err.is_retryable() {
+            self.max_attempts
+        } else {
+            1
+        }
+    }
+
+    /// Delay before the next attempt after `attempts` attempts failed, the last one with `err`,
+    /// before jitter. `None` when the slot should not be attempted again: skipped, pruned and
+    /// undecodable slots never are, and rate limits back off from their own, longer delay.
+    pub fn backoff(&self, err: &IngestError, attempts: u32) -> Option<Duration> {
+        if !err.is_retryable() || attempts >= self.max_attempts {
+            return None;
+        }
+        let base = match err {
+            IngestError::RateLimited(_) => self.rate_limit_delay,
+            _ => self.base_delay,
+        };
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        Some(base.saturating_mul(factor).min(self.max_delay))
+    }
+
+    /// `backoff` with jitter, so slots that failed together are not retried together.
+    /// The delay is drawn from the upper half of the backoff.
+    pub fn next_delay(&self, err: &IngestError, attempts: u32) -> Option<Duration> {
+        let backoff = self.backoff(err, attempts)?;
+        let half = backoff / 2;
+        Some(half + half.mul_f64(rand::thread_rng().gen::<f64>()))
+    }
+
+    /// Run `operation` for `slot` until it succeeds, fails with an error that is not retried, or
+    /// runs out of attempts. The last error is returned.
+    pub async fn retry<T, F, Fut>(&self, slot: u64, operation: F) -> Result<T, IngestError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, IngestError>>,
+    {
+        self.retry_if(slot, |_| true, operation).await
+    }
+
+    /// `retry`, except that errors for which `should_retry` returns false are returned right away
+    pub async fn retry_if<T, F, Fut>(&self, slot: u64, should_retry: impl Fn(&IngestError) -> bool, mut operation: F) -> Result<T, IngestError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, IngestError>>,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e) => match self.next_delay(&e, attempts).filter(|_| should_retry(&e)) {
+                    Some(delay) => {
+                        eprintln!("WARNING: Attempt {} for slot {} failed: {}, retrying in {:?}", attempts, slot, e, delay);
+                        tokio::time::sleep(delay).await;
+                    }
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+}
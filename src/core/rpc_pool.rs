@@ -0,0 +1,293 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcBlockConfig;
+use solana_client::rpc_custom_error::{
+    JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP, JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+    JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY, JSON_RPC_SERVER_ERROR_SLOT_SKIPPED,
+};
+use solana_client::rpc_request::RpcError;
+use solana_client::rpc_response::RpcLeaderSchedule;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::epoch_schedule::EpochSchedule;
+use solana_transaction_status::UiConfirmedBlock;
+use super::config::RpcEndpointConfig;
+
+// Weight of the newest sample in the latency/error moving averages
+const EWMA_ALPHA: f64 = 0.2;
+const RATE_LIMIT_BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// Consecutive endpoint failures after which the endpoint is skipped for a while
+pub const CIRCUIT_BREAKER_THRESHOLD: u32 = 3;
+const CIRCUIT_BREAKER_BASE_BACKOFF: Duration = Duration::from_secs(5);
+
+pub type RpcFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, ClientError>> + Send + 'a>>;
+
+/// Rolling health statistics for a single RPC endpoint
+#[derive(Debug, Clone, Default)]
+pub struct EndpointHealth {
+    pub latency_ms: f64,               // Moving average latency of successful requests
+    pub error_rate: f64,               // Moving average of failed requests, 0.0 to 1.0
+    pub requests: u64,
+    pub failures: u64,
+    pub rate_limits: u32,              // Consecutive 429 responses, reset on success
+    pub consecutive_failures: u32,     // Consecutive endpoint failures other than 429s, reset on success
+    pub backoff_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    pub fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        self.latency_ms = if self.requests == 0 {
+            latency_ms
+        } else {
+            EWMA_ALPHA * latency_ms + (1.0 - EWMA_ALPHA) * self.latency_ms
+        };
+        self.error_rate *= 1.0 - EWMA_ALPHA;
+        self.requests += 1;
+        self.rate_limits = 0;
+        self.consecutive_failures = 0;
+        self.backoff_until = None;
+    }
+
+    fn count_failure(&mut self) {
+        self.error_rate = EWMA_ALPHA + (1.0 - EWMA_ALPHA) * self.error_rate;
+        self.requests += 1;
+        self.failures += 1;
+    }
+
+    /// Open the circuit once the endpoint failed `CIRCUIT_BREAKER_THRESHOLD` times in a row:
+    /// it is skipped for 5s, doubling with every further failure up to 60s, then tried again
+    pub fn record_failure(&mut self, now: Instant) {
+        self.count_failure();
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= CIRCUIT_BREAKER_THRESHOLD {
+            let backoff = CIRCUIT_BREAKER_BASE_BACKOFF
+                .saturating_mul(1 << (self.consecutive_failures - CIRCUIT_BREAKER_THRESHOLD).min(6))
+                .min(MAX_BACKOFF);
+            self.backoff_until = Some(now + backoff);
+        }
+    }
+
+    /// Back off exponentially (1s, 2s, 4s, ... capped at 60s) after consecutive 429s
+    pub fn record_rate_limit(&mut self, now: Instant) {
+        self.count_failure();
+        let backoff = RATE_LIMIT_BASE_BACKOFF
+            .saturating_mul(1 << self.rate_limits.min(6))
+            .min(MAX_BACKOFF);
+        self.rate_limits += 1;
+        self.backoff_until = Some(now + backoff);
+    }
+
+    pub fn is_backing_off(&self, now: Instant) -> bool {
+        self.backoff_until.map(|until| now < until).unwrap_or(false)
+    }
+
+    /// Lower is better: latency inflated by the recent error rate
+    pub fn score(&self) -> f64 {
+        self.latency_ms.max(1.0) * (1.0 + 10.0 * self.error_rate)
+    }
+}
+
+struct Endpoint {
+    url: String,
+    priority: u32,
+    client: RpcClient,
+    health: Mutex<EndpointHealth>,
+}
+
+/// A set of RPC endpoints that requests are spread across, with automatic failover
+pub struct RpcPool {
+    endpoints: Vec<Endpoint>,
+    commitment: CommitmentConfig,
+}
+
+/// Scheme and host of an endpoint URL, leaving out paths that usually carry API keys
+pub fn redact_url(url: &str) -> String {
+    let host_start = url.find("://").map(|i| i + 3).unwrap_or(0);
+    match url[host_start..].find('/') {
+        Some(path_start) => format!("{}/...", &url[..host_start + path_start]),
+        None => url.to_string(),
+    }
+}
+
+// Code some providers send for every server error, which says nothing about the cause
+const GENERIC_SERVER_ERROR: i64 = -32000;
+
+/// The JSON-RPC error code of `err`, if the RPC sent one that identifies the error
+fn rpc_error_code(err: &ClientError) -> Option<i64> {
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) if *code != GENERIC_SERVER_ERROR => Some(*code),
+        _ => None,
+    }
+}
+
+/// Lowercased message of an RPC error that came without an identifying code. Classifying by
+/// message is only a fallback for providers that send no code (or only the generic one).
+fn uncoded_error_message(err: &ClientError) -> Option<String> {
+    if rpc_error_code(err).is_some() {
+        return None;
+    }
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { message, .. })
+        | ClientErrorKind::RpcError(RpcError::RpcRequestError(message))
+        | ClientErrorKind::RpcError(RpcError::ForUser(message)) => Some(message.to_lowercase()),
+        _ => None,
+    }
+}
+
+/// Whether the RPC rejected the request because of rate limiting: HTTP status 429 or error code 429
+pub fn is_rate_limited(err: &ClientError) -> bool {
+    if let ClientErrorKind::Reqwest(e) = err.kind() {
+        return e.status().is_some_and(|s| s.as_u16() == 429);
+    }
+    match rpc_error_code(err) {
+        Some(code) => code == 429,
+        None => uncoded_error_message(err).is_some_and(|message| message.contains("too many requests")),
+    }
+}
+
+/// Whether the error is a definitive answer about the slot that every endpoint would give
+pub fn is_slot_skipped(err: &ClientError) -> bool {
+    matches!(
+        rpc_error_code(err),
+        Some(JSON_RPC_SERVER_ERROR_SLOT_SKIPPED | JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED)
+    )
+}
+
+/// Whether the endpoint no longer holds the slot's block because it was cleaned up from its ledger
+pub fn is_slot_pruned(err: &ClientError) -> bool {
+    match rpc_error_code(err) {
+        Some(code) => code == JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP,
+        None => uncoded_error_message(err).is_some_and(|message| message.contains("cleaned up") || message.contains("does not exist")),
+    }
+}
+
+/// Whether the endpoint does not have the slot's block yet, as happens at the chain tip
+pub fn is_block_not_available(err: &ClientError) -> bool {
+    rpc_error_code(err) == Some(JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE)
+}
+
+/// Whether the error says something about the endpoint itself rather than the requested data
+fn is_endpoint_failure(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+        _ => true,
+    }
+}
+
+impl RpcPool {
+    pub fn new(endpoints: &[RpcEndpointConfig], commitment: CommitmentConfig) -> Self {
+        let clients = endpoints
+            .iter()
+            .map(|e| (e.url.clone(), e.priority, RpcClient::new_with_commitment(e.url.clone(), commitment)))
+            .collect();
+        Self::from_clients(clients, commitment)
+    }
+
+    /// Build a pool from existing clients as `(url, priority, client)`
+    pub fn from_clients(clients: Vec<(String, u32, RpcClient)>, commitment: CommitmentConfig) -> Self {
+        let endpoints = clients
+            .into_iter()
+            .map(|(url, priority, client)| Endpoint {
+                url,
+                priority,
+                client,
+                health: Mutex::new(EndpointHealth::default()),
+            })
+            .collect();
+        RpcPool { endpoints, commitment }
+    }
+
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    /// Current health of every endpoint as `(url, health)`
+    pub fn health(&self) -> Vec<(String, EndpointHealth)> {
+        self.endpoints
+            .iter()
+            .map(|e| (e.url.clone(), e.health.lock().unwrap().clone()))
+            .collect()
+    }
+
+    /// Endpoint indices in the order they should be tried: endpoints that are not backing off first,
+    /// then by configured priority, then by health score
+    fn ordered_endpoints(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut keyed: Vec<(bool, u32, f64, usize)> = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(idx, e)| {
+                let health = e.health.lock().unwrap();
+                (health.is_backing_off(now), e.priority, health.score(), idx)
+            })
+            .collect();
+        keyed.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.cmp(&b.1))
+                .then(a.2.total_cmp(&b.2))
+        });
+        keyed.into_iter().map(|(_, _, _, idx)| idx).collect()
+    }
+
+    /// Run `request` against the endpoints in order until one succeeds
+    pub async fn call<T, F>(&self, method: &str, request: F) -> Result<T, ClientError>
+    where
+        F: for<'c> Fn(&'c RpcClient) -> RpcFuture<'c, T>,
+    {
+        let mut last_error = None;
+        for idx in self.ordered_endpoints() {
+            let endpoint = &self.endpoints[idx];
+            let started = Instant::now();
+            match request(&endpoint.client).await {
+                Ok(value) => {
+                    endpoint.health.lock().unwrap().record_success(started.elapsed());
+                    return Ok(value);
+                }
+                Err(e) => {
+                    if is_slot_skipped(&e) {
+                        return Err(e);
+                    }
+                    if is_rate_limited(&e) {
+                        eprintln!("WARNING: {} rate limited by {}, backing off", method, redact_url(&endpoint.url));
+                        endpoint.health.lock().unwrap().record_rate_limit(Instant::now());
+                    } else if is_endpoint_failure(&e) {
+                        let mut health = endpoint.health.lock().unwrap();
+                        health.record_failure(Instant::now());
+                        if health.consecutive_failures == CIRCUIT_BREAKER_THRESHOLD {
+                            eprintln!("WARNING: {} failed {} times in a row, backing off", redact_url(&endpoint.url), CIRCUIT_BREAKER_THRESHOLD);
+                        }
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| ClientErrorKind::Custom("No RPC endpoints configured".to_string()).into()))
+    }
+
+    pub async fn get_slot(&self) -> Result<u64, ClientError> {
+        self.call("getSlot", |client| Box::pin(client.get_slot())).await
+    }
+
+    pub async fn get_block_with_config(&self, slot: u64, config: RpcBlockConfig) -> Result<UiConfirmedBlock, ClientError> {
+        self.call("getBlock", move |client| Box::pin(client.get_block_with_config(slot, config))).await
+    }
+
+    /// Slots in `start_slot..=end_slot` that have a block, at the client's commitment
+    pub async fn get_blocks(&self, start_slot: u64, end_slot: u64) -> Result<Vec<u64>, ClientError> {
+        self.call("getBlocks", move |client| Box::pin(client.get_blocks(start_slot, Some(end_slot)))).await
+    }
+
+    pub async fn get_epoch_schedule(&self) -> Result<EpochSchedule, ClientError> {
+        self.call("getEpochSchedule", |client| Box::pin(client.get_epoch_schedule())).await
+    }
+
+    pub async fn get_leader_schedule(&self, slot: u64) -> Result<Option<RpcLeaderSchedule>, ClientError> {
+        self.call("getLeaderSchedule", move |client| Box::pin(client.get_leader_schedule(Some(slot)))).await
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use arrow_schema::{DataType, Field, Schema};
+use clickhouse::{Client, Row};
+use futures::future::BoxFuture;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+use serde::Serialize;
+use crate::analyzer::bundling::BundlingAnalysisRow;
+use crate::analyzer::columns::{Column, ColumnType};
+use crate::analyzer::fee_by_type::FeeByTransactionTypeRow;
+use crate::analyzer::fee_landscape::FeeLandscapeRow;
+use crate::analyzer::program_fee::ProgramFeeAnalysisRow;
+use crate::analyzer::registry::RowBatch;
+use crate::analyzer::tip_candidates::TipAccountCandidateRow;
+use crate::analyzer::transactions::TransactionRow;
+use super::backfill::BackfillProgressRow;
+use super::block_fetcher::BlockRow;
+use super::failed_slots::FailedSlotRow;
+use super::slot_status::SlotStatusRow;
+
+pub type SinkError = Box<dyn std::error::Error + Send + Sync>;
+
+// A Parquet file is written once a table has buffered this many rows...
+pub const PARQUET_ROWS_PER_FILE: usize = 100_000;
+// ...or its oldest buffered row is this old, so streaming output shows up regularly
+pub const PARQUET_FILE_INTERVAL: Duration = Duration::from_secs(60);
+// How often buffered rows are checked for being due, whether or not new rows arrive
+pub const FLUSH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Destination for block and analysis rows, one table at a time
+pub trait Sink: Send + Sync {
+    fn write<'a>(&'a self, table: &'a str, rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>>;
+
+    /// Persist anything still buffered
+    fn flush(&self) -> BoxFuture<'_, Result<(), SinkError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Persist buffered rows that are due, for sinks that buffer. Called periodically by
+    /// `flush_periodically` so rows are written even when no new rows arrive.
+    fn flush_due(&self) -> BoxFuture<'_, Result<(), SinkError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Highest slot in the `blocks` table, for sinks that can be read back
+    fn last_block_slot(&self) -> BoxFuture<'_, Option<u64>> {
+        Box::pin(async { None })
+    }
+
+    /// Last slot recorded for the backfill of `start_slot..=end_slot`, for sinks that can be read back
+    fn backfill_progress(&self, _start_slot: u64, _end_slot: u64) -> BoxFuture<'_, Option<u64>> {
+        Box::pin(async { None })
+    }
+
+    /// Slots in `start_slot..=end_slot` stored in the `blocks` table, in ascending order
+    fn stored_slots(&self, _start_slot: u64, _end_slot: u64) -> BoxFuture<'_, Result<Vec<u64>, SinkError>> {
+        Box::pin(async { Err("this sink is write-only, stored blocks cannot be read back".into()) })
+    }
+
+    /// Slots in the `failed_slots` table that have not been recovered yet, in ascending order
+    fn failed_slots(&self) -> BoxFuture<'_, Result<Vec<FailedSlotRow>, SinkError>> {
+        Box::pin(async { Err("this sink is write-only, failed slots cannot be read back".into()) })
+    }
+    /// The latest stored row per key of `table`, for the tables of stateful analyzers
+    fn read_rows<'a>(&'a self, _table: &'a str) -> BoxFuture<'a, Result<Box<dyn RowBatch>, SinkError>> {
+        Box::pin(async { Err("this sink is write-only, stored rows cannot be read back".into()) })
+    }
+}
+
+/// Writes rows into ClickHouse tables of the same name
+pub struct ClickHouseSink {
+    client: Client,
+}
+
+impl ClickHouseSink {
+    pub fn new(client: Client) -> Self {
+        ClickHouseSink { client }
+    }
+
+    /// Insert `rows` into `table` as rows of type `T`. Columns `T` does not have, such as
+    /// `ingest_version`, take their defaults.
+    async fn insert<T: Row + Serialize + 'static>(&self, table: &str, rows: &dyn RowBatch) -> Result<(), SinkError> {
+        let rows = rows
+            .as_any()
+            .downcast_ref::<Vec<T>>()
+            .ok_or_else(|| format!("rows for table {} are not of type {}", table, std::any::type_name::<T>()))?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let mut insert = self.client.insert::<T>(table)?;
+        for row in rows {
+            insert.write(row).await?;
+        }
+        insert.end().await?;
+        Ok(())
+    }
+}
+
+impl Sink for ClickHouseSink {
+    fn write<'a>(&'a self, table: &'a str, rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            // Each table is inserted with the row type that produces it
+            match table {
+                "blocks" => self.insert::<BlockRow>(table, rows).await,
+                "backfill_progress" => self.insert::<BackfillProgressRow>(table, rows).await,
+                "slot_status" => self.insert::<SlotStatusRow>(table, rows).await,
+                "failed_slots" => self.insert::<FailedSlotRow>(table, rows).await,
+                "bundling_analysis" => self.insert::<BundlingAnalysisRow>(table, rows).await,
+                "fee_landscape" => self.insert::<FeeLandscapeRow>(table, rows).await,
+                "program_fee_analysis" => self.insert::<ProgramFeeAnalysisRow>(table, rows).await,
+                "fee_by_transaction_type" => self.insert::<FeeByTransactionTypeRow>(table, rows).await,
+                "tip_account_candidates" => self.insert::<TipAccountCandidateRow>(table, rows).await,
+                "transactions" => self.insert::<TransactionRow>(table, rows).await,
+                _ => Err(format!("no ClickHouse row type is known for table {}", table).into()),
+            }
+        })
+    }
+
+    fn last_block_slot(&self) -> BoxFuture<'_, Option<u64>> {
+        Box::pin(async move {
+            self.client
+                .query("SELECT slot FROM blocks ORDER BY slot DESC LIMIT 1")
+                .fetch_optional()
+                .await
+                .unwrap_or(None)
+        })
+    }
+
+    fn backfill_progress(&self, start_slot: u64, end_slot: u64) -> BoxFuture<'_, Option<u64>> {
+        Box::pin(async move {
+            self.client
+                .query("SELECT last_slot FROM backfill_progress WHERE start_slot = ? AND end_slot = ? ORDER BY last_slot DESC LIMIT 1")
+                .bind(start_slot)
+                .bind(end_slot)
+                .fetch_optional()
+                .await
+                .unwrap_or(None)
+        })
+    }
+
+    fn stored_slots(&self, start_slot: u64, end_slot: u64) -> BoxFuture<'_, Result<Vec<u64>, SinkError>> {
+        Box::pin(async move {
+            let slots = self.client
+                .query("SELECT DISTINCT slot FROM blocks WHERE slot >= ? AND slot <= ? ORDER BY slot")
+                .bind(start_slot)
+                .bind(end_slot)
+                .fetch_all()
+                .await?;
+            Ok(slots)
+        })
+    }
+
+    fn failed_slots(&self) -> BoxFuture<'_, Result<Vec<FailedSlotRow>, SinkError>> {
+        Box::pin(async move {
+            let rows = self.client
+                .query("SELECT ?fields FROM failed_slots FINAL WHERE resolved = 0 ORDER BY slot")
+                .fetch_all::<FailedSlotRow>()
+                .await?;
+            Ok(rows)
+        })
+    }
+
+    fn read_rows<'a>(&'a self, table: &'a str) -> BoxFuture<'a, Result<Box<dyn RowBatch>, SinkError>> {
+        Box::pin(async move {
+            match table {
+                "tip_account_candidates" => {
+                    let rows = self.client
+                        .query("SELECT ?fields FROM tip_account_candidates FINAL")
+                        .fetch_all::<TipAccountCandidateRow>()
+                        .await?;
+                    Ok(Box::new(rows) as Box<dyn RowBatch>)
+                }
+                _ => Err(format!("rows of table {} cannot be read back", table).into()),
+            }
+        })
+    }
+}
+
+/// Appends rows as newline-delimited JSON to `<directory>/<table>.jsonl`
+pub struct JsonlSink {
+    directory: PathBuf,
+    files: Mutex<HashMap<String, BufWriter<File>>>,
+}
+
+impl JsonlSink {
+    pub fn new(directory: &Path) -> Result<Self, SinkError> {
+        std::fs::create_dir_all(directory)
+            .map_err(|e| format!("Failed to create output directory {}: {}", directory.display(), e))?;
+        Ok(JsonlSink {
+            directory: directory.to_path_buf(),
+            files: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn append(&self, table: &str, rows: &[Value]) -> Result<(), SinkError> {
+        let mut files = self.files.lock().unwrap();
+        if !files.contains_key(table) {
+            let path = self.directory.join(format!("{}.jsonl", table));
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+            files.insert(table.to_string(), BufWriter::new(file));
+        }
+        let writer = files.get_mut(table).unwrap();
+        for row in rows {
+            serde_json::to_writer(&mut *writer, row)?;
+            writer.write_all(b"\n")?;
+        }
+        // Every block is on disk before the next one is processed
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Sink for JsonlSink {
+    fn write<'a>(&'a self, table: &'a str, rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            self.append(table, &rows.to_json()?)
+        })
+    }
+}
+
+#[derive(Default)]
+struct ParquetBuffer {
+    rows: Vec<Value>,
+    started: Option<Instant>,
+}
+
+/// Buffers rows per table and writes them to `<directory>/<table>/<table>-<unix millis>-<seq>.parquet`.
+/// Every file of a table has the schema of the table's row type.
+pub struct ParquetSink {
+    directory: PathBuf,
+    buffers: Mutex<HashMap<String, ParquetBuffer>>,
+    schemas: Mutex<HashMap<String, Arc<Schema>>>,
+    files_written: Mutex<u64>,
+}
+
+impl ParquetSink {
+    pub fn new(directory: &Path) -> Result<Self, SinkError> {
+        std::fs::create_dir_all(directory)
+            .map_err(|e| format!("Failed to create output directory {}: {}", directory.display(), e))?;
+        Ok(ParquetSink {
+            directory: directory.to_path_buf(),
+            buffers: Mutex::new(HashMap::new()),
+            schemas: Mutex::new(HashMap::new()),
+            files_written: Mutex::new(0),
+        })
+    }
+
+    /// Remember the schema of `table` from its first batch, and reject batches of another row type
+    fn check_schema(&self, table: &str, rows: &dyn RowBatch) -> Result<(), SinkError> {
+        let schema = Arc::new(arrow_schema(&rows.columns()?));
+        let mut schemas = self.schemas.lock().unwrap();
+        let expected = schemas.entry(table.to_string()).or_insert_with(|| schema.clone());
+        if *expected != schema {
+            return Err(format!("Rows written to {} do not match the schema of its earlier rows", table).into());
+        }
+        Ok(())
+    }
+
+    fn write_file(&self, table: &str, rows: &[Value]) -> Result<(), SinkError> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let schema = match self.schemas.lock().unwrap().get(table) {
+            Some(schema) => schema.clone(),
+            None => return Err(format!("No schema known for {}", table).into()),
+        };
+        let table_directory = self.directory.join(table);
+        std::fs::create_dir_all(&table_directory)?;
+
+        let sequence = {
+            let mut files_written = self.files_written.lock().unwrap();
+            *files_written += 1;
+            *files_written
+        };
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+        let path = table_directory.join(format!("{}-{}-{}.parquet", table, millis, sequence));
+
+        let mut decoder = arrow_json::ReaderBuilder::new(schema.clone()).build_decoder()?;
+        decoder.serialize(rows)?;
+        let batch = match decoder.flush()? {
+            Some(batch) => batch,
+            None => return Ok(()),
+        };
+
+        let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let mut writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+    /// Take the buffered rows of every table, or only of those that are due for a file
+    fn take_buffers(&self, only_due: bool) -> Vec<(String, Vec<Value>)> {
+        let mut buffers = self.buffers.lock().unwrap();
+        buffers
+            .iter_mut()
+            .filter(|(_, buffer)| {
+                !only_due
+                    || buffer.rows.len() >= PARQUET_ROWS_PER_FILE
+                    || buffer.started.is_some_and(|started| started.elapsed() >= PARQUET_FILE_INTERVAL)
+            })
+            .map(|(table, buffer)| {
+                buffer.started = None;
+                (table.clone(), std::mem::take(&mut buffer.rows))
+            })
+            .collect()
+    }
+
+    /// Put rows whose file could not be written back in front of the rows buffered since
+    fn return_buffer(&self, table: String, mut rows: Vec<Value>) {
+        let mut buffers = self.buffers.lock().unwrap();
+        let buffer = buffers.entry(table).or_default();
+        rows.append(&mut buffer.rows);
+        buffer.rows = rows;
+        buffer.started = Some(Instant::now());
+    }
+
+    /// Write a file for the buffered rows of every table, or only of those that are due,
+    /// keeping the rows of any table whose file fails so the next flush retries them
+    fn write_buffers(&self, only_due: bool) -> Result<(), SinkError> {
+        let mut result = Ok(());
+        for (table, rows) in self.take_buffers(only_due) {
+            if let Err(e) = self.write_file(&table, &rows) {
+                if result.is_ok() {
+                    result = Err(format!("Failed to write {} rows: {}", table, e).into());
+                }
+                self.return_buffer(table, rows);
+            }
+        }
+        result
+    }
+}
+
+impl Sink for ParquetSink {
+    fn write<'a>(&'a self, table: &'a str, rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            self.check_schema(table, rows)?;
+            let rows = rows.to_json()?;
+            {
+                let mut buffers = self.buffers.lock().unwrap();
+                let buffer = buffers.entry(table.to_string()).or_default();
+                buffer.started.get_or_insert_with(Instant::now);
+                buffer.rows.extend(rows);
+            }
+            self.flush_due().await
+        })
+    }
+
+    fn flush(&self) -> BoxFuture<'_, Result<(), SinkError>> {
+        Box::pin(async move { self.write_buffers(false) })
+    }
+
+    fn flush_due(&self) -> BoxFuture<'_, Result<(), SinkError>> {
+        Box::pin(async move { self.write_buffers(true) })
+    }
+}
+
+/// Call `flush_due` on `sink` every `interval`, until the task is aborted
+pub async fn flush_periodically(sink: Arc<dyn Sink>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if let Err(e) = sink.flush_due().await {
+            eprintln!("WARNING: Failed to flush buffered rows: {}", e);
+        }
+    }
+}
+
+/// Arrow schema for rows with `columns`
+pub fn arrow_schema(columns: &[Column]) -> Schema {
+    let fields: Vec<Field> = columns
+        .iter()
+        .map(|column| Field::new(column.name, arrow_data_type(&column.column_type), column.nullable))
+        .collect();
+    Schema::new(fields)
+}
+
+fn arrow_data_type(column_type: &ColumnType) -> DataType {
+    match column_type {
+        ColumnType::Bool => DataType::Boolean,
+        ColumnType::UInt8 => DataType::UInt8,
+        ColumnType::UInt16 => DataType::UInt16,
+        ColumnType::UInt32 => DataType::UInt32,
+        ColumnType::UInt64 => DataType::UInt64,
+        ColumnType::Int8 => DataType::Int8,
+        ColumnType::Int16 => DataType::Int16,
+        ColumnType::Int32 => DataType::Int32,
+        ColumnType::Int64 => DataType::Int64,
+        ColumnType::Float32 => DataType::Float32,
+        ColumnType::Float64 => DataType::Float64,
+        ColumnType::String => DataType::Utf8,
+        ColumnType::List(item) => DataType::List(Arc::new(Field::new("item", arrow_data_type(item), true))),
+    }
+}
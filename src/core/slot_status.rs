@@ -0,0 +1,61 @@
+use clickhouse::Row;
+use serde::{Deserialize, Serialize};
+use super::block_fetcher::BlockFetcher;
+use super::ingest_error::IngestError;
+use super::sink::Sink;
+
+/// What happened to a slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotStatus {
+    Produced,     // The block was fetched
+    Skipped,      // The leader produced no block
+    Unavailable,  // A block may exist but could not be fetched
+}
+
+impl SlotStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlotStatus::Produced => "produced",
+            SlotStatus::Skipped => "skipped",
+            SlotStatus::Unavailable => "unavailable",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Row, Serialize, Deserialize)]
+pub struct SlotStatusRow {
+    pub slot: u64,
+    pub epoch: Option<u64>,
+    pub status: String,
+    pub leader: Option<String>,  // Scheduled leader, whether or not it produced the block
+    pub error: String,
+}
+
+/// Only an RPC answer that the slot was skipped counts as skipped; every other failure leaves the
+/// slot unavailable, since the block may still exist
+pub fn classify_fetch_error(err: &IngestError) -> SlotStatus {
+    match err {
+        IngestError::SlotSkipped(_) => SlotStatus::Skipped,
+        _ => SlotStatus::Unavailable,
+    }
+}
+
+/// Build the status row of `slot`, looking up its epoch and scheduled leader
+pub async fn slot_status_row(fetcher: &BlockFetcher, slot: u64, status: SlotStatus, error: String) -> SlotStatusRow {
+    SlotStatusRow {
+        slot,
+        epoch: fetcher.epoch(slot).await,
+        status: status.as_str().to_string(),
+        leader: fetcher.scheduled_leader(slot).await,
+        error,
+    }
+}
+
+/// Record the outcome of `slot` in the `slot_status` table. Failing to record it is only logged,
+/// so it never stops ingestion.
+pub async fn record_slot_status(sink: &dyn Sink, fetcher: &BlockFetcher, slot: u64, status: SlotStatus, error: String) {
+    let row = slot_status_row(fetcher, slot, status, error).await;
+    if let Err(e) = sink.write("slot_status", &vec![row]).await {
+        eprintln!("WARNING: Failed to record status of slot {}: {}", slot, e);
+    }
+}
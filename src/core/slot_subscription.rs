@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use futures::StreamExt;
+use solana_client::nonblocking::pubsub_client::{PubsubClient, PubsubClientError};
+use solana_client::rpc_config::{RpcBlockSubscribeConfig, RpcBlockSubscribeFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_transaction_status::TransactionDetails;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use super::config::SubscriptionKind;
+use super::rpc_pool::redact_url;
+
+pub const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+pub const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Latest slot announced over a PubSub WebSocket subscription, kept up to date by a background task
+/// that reconnects with exponential backoff whenever the socket drops
+pub struct SlotSubscription {
+    receiver: watch::Receiver<u64>,
+    connected: Arc<AtomicBool>,
+    authoritative: bool,
+    task: JoinHandle<()>,
+}
+
+impl SlotSubscription {
+    pub fn spawn(url: &str, kind: SubscriptionKind, commitment: CommitmentConfig) -> Self {
+        let (sender, receiver) = watch::channel(0);
+        let connected = Arc::new(AtomicBool::new(false));
+        let task = tokio::spawn(run_subscription(url.to_string(), kind, commitment, sender, Arc::clone(&connected)));
+        SlotSubscription {
+            receiver,
+            connected,
+            // slotSubscribe reports processed slots; only its root is known to be finalized
+            authoritative: kind == SubscriptionKind::Block || commitment.is_finalized(),
+            task,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Whether a notified slot is already available at the configured commitment. If not,
+    /// a notification only means the chain has moved and the tip must still be read from the RPC.
+    pub fn is_authoritative(&self) -> bool {
+        self.authoritative
+    }
+
+    /// Wait up to `timeout` for a slot newer than the last one returned
+    pub async fn wait_for_slot(&mut self, timeout: Duration) -> Option<u64> {
+        match tokio::time::timeout(timeout, self.receiver.changed()).await {
+            Ok(Ok(())) => Some(*self.receiver.borrow_and_update()),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for SlotSubscription {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn run_subscription(
+    url: String,
+    kind: SubscriptionKind,
+    commitment: CommitmentConfig,
+    sender: watch::Sender<u64>,
+    connected: Arc<AtomicBool>,
+) {
+    let mut delay = INITIAL_RECONNECT_DELAY;
+    loop {
+        match subscribe(&url, kind, commitment, &sender, &connected).await {
+            Ok(()) => eprintln!("WARNING: Slot subscription to {} closed, polling until it reconnects", redact_url(&url)),
+            Err(e) => eprintln!("WARNING: Slot subscription to {} failed: {}, polling until it reconnects", redact_url(&url), e),
+        }
+        // A connection that got as far as subscribing starts the backoff over
+        if connected.swap(false, Ordering::Relaxed) {
+            delay = INITIAL_RECONNECT_DELAY;
+        }
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+/// Publish a slot unless a newer one has already been published
+fn publish(sender: &watch::Sender<u64>, slot: u64) {
+    sender.send_if_modified(|latest| {
+        if slot > *latest {
+            *latest = slot;
+            true
+        } else {
+            false
+        }
+    });
+}
+
+/// Subscribe once and publish notified slots until the socket closes
+async fn subscribe(
+    url: &str,
+    kind: SubscriptionKind,
+    commitment: CommitmentConfig,
+    sender: &watch::Sender<u64>,
+    connected: &AtomicBool,
+) -> Result<(), PubsubClientError> {
+    let client = PubsubClient::new(url).await?;
+    match kind {
+        SubscriptionKind::Slot => {
+            let (mut notifications, _unsubscribe) = client.slot_subscribe().await?;
+            connected.store(true, Ordering::Relaxed);
+            println!("Subscribed to slot notifications from {}", redact_url(url));
+            while let Some(info) = notifications.next().await {
+                publish(sender, if commitment.is_finalized() { info.root } else { info.slot });
+            }
+        }
+        SubscriptionKind::Block => {
+            let config = RpcBlockSubscribeConfig {
+                commitment: Some(commitment),
+                encoding: None,
+                // Only the slot is needed, the block itself is fetched through the RPC pool
+                transaction_details: Some(TransactionDetails::None),
+                show_rewards: Some(false),
+                max_supported_transaction_version: Some(0),
+            };
+            let (mut notifications, _unsubscribe) = client.block_subscribe(RpcBlockSubscribeFilter::All, Some(config)).await?;
+            connected.store(true, Ordering::Relaxed);
+            println!("Subscribed to block notifications from {}", redact_url(url));
+            while let Some(update) = notifications.next().await {
+                if update.value.err.is_none() {
+                    publish(sender, update.value.slot);
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,147 +1,374 @@
-use crate::analyzer::types::Transaction;
+use crate::analyzer::types::{TipTransfer, Transaction};
 use crate::analyzer::landing_services::identify_landing_service;
-use solana_transaction_status::{EncodedTransaction, UiInstruction, UiMessage, UiParsedInstruction};
+use solana_sdk::bs58;
+use solana_sdk::transaction::VersionedTransaction;
+use solana_transaction_status::{
+    EncodedTransaction, EncodedTransactionWithStatusMeta, UiInnerInstructions, UiInstruction, UiLoadedAddresses,
+    UiMessage, UiParsedInstruction, UiTransaction, UiTransactionStatusMeta,
+};
 
-pub fn parse_transactions(
-    block_transactions: &[solana_transaction_status::EncodedTransactionWithStatusMeta],
-    slot: u64,
-) -> Vec<Transaction> {
-    let mut transactions = Vec::new();
-    
-    for (idx, transaction_with_meta) in block_transactions.iter().enumerate() {
-        let position = (idx + 1) as u32;
-        
-        let (recent_blockhash, fee, failed, has_compute_budget, is_vote, is_system, program_ids, landing_service, tip_recipient, tip_amount) = match &transaction_with_meta.transaction {
-            EncodedTransaction::Json(transaction_json) => {
-                let message = &transaction_json.message;
-                
-                let recent_blockhash = match message {
-                    UiMessage::Parsed(parsed_message) => parsed_message.recent_blockhash.clone(),
-                    UiMessage::Raw(raw_message) => raw_message.recent_blockhash.clone(),
-                };
-                
-                let mut landing_service_found = String::new();
-                let mut tip_recipient = String::new();
-                let mut tip_amount = 0u64;
-                
-                let program_ids: Vec<String> = match message {
-                    UiMessage::Parsed(parsed_message) => {
-                        parsed_message.instructions.iter().map(|inst| {
-                            match inst {
-                                UiInstruction::Parsed(parsed_instruction) => {
-                                    match parsed_instruction {
-                                        UiParsedInstruction::Parsed(instruction_parsed) => {
-                                            // Check if this is a System Program transfer to a known tip account
-                                            if instruction_parsed.program_id == "11111111111111111111111111111111" {
-                                                // The parsed field is already a Value, try to extract transfer info
-                                                if let Some(parsed_data) = instruction_parsed.parsed.as_object() {
-                                                    // Check if it's a transfer instruction
-                                                    if let Some(inst_type) = parsed_data.get("type").and_then(|v| v.as_str()) {
-                                                        if inst_type == "transfer" {
-                                                            // Extract destination address and amount
-                                                            if let Some(info) = parsed_data.get("info").and_then(|v| v.as_object()) {
-                                                                if let Some(destination) = info.get("destination").and_then(|v| v.as_str()) {
-                                                                    // Extract tip amount (lamports)
-                                                                    if let Some(lamports) = info.get("lamports").and_then(|v| v.as_u64()) {
-                                                                        // Set tip_recipient for ANY System Program transfer (not just known tip accounts)
-                                                                        // This allows us to detect unknown landing services
-                                                                        if tip_recipient.is_empty() && lamports > 0 {
-                                                                            tip_recipient = destination.to_string();
-                                                                            tip_amount = lamports;
-                                                                        }
-                                                                        
-                                                                        // Check if this is a known landing service address
-                                                                        if let Some(service) = identify_landing_service(destination) {
-                                                                            if landing_service_found.is_empty() {
-                                                                                landing_service_found = service;
-                                                                            }
-                                                                        } else if !tip_recipient.is_empty() && tip_recipient == destination {
-                                                                            // Unknown landing service - set to "Unknown: {address}" format
-                                                                            // This matches the format used in bundling.rs
-                                                                            if landing_service_found.is_empty() {
-                                                                                landing_service_found = format!("Unknown: {}", destination);
-                                                                            }
-                                                                        }
-                                                                    }
-                                                                }
-                                                            }
-                                                        }
-                                                    }
-                                                }
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+// SystemInstruction::Transfer is bincode-encoded as the u32 variant index 2 followed by the u64 lamports
+const SYSTEM_TRANSFER_TAG: u32 = 2;
+// ComputeBudgetInstruction is borsh-encoded as a u8 variant index followed by its argument
+const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+// Landing service classifications for transactions that did not tip a known landing service
+pub const NO_TIP: &str = "No Tip";
+pub const PLAIN_TRANSFER: &str = "Plain Transfer";
+
+/// Transaction-level fields read from the message
+#[derive(Default)]
+struct ScannedMessage {
+    signature: String,
+    signature_count: usize,
+    fee_payer: String,
+    recent_blockhash: String,
+    program_ids: Vec<String>,
+}
+
+/// Collects the transfers and compute budget settings of a transaction while its instructions are scanned
+#[derive(Default)]
+struct InstructionScan {
+    transfers: Vec<TipTransfer>,
+    compute_unit_price: u64,
+    compute_unit_limit: u32,
+}
+
+impl InstructionScan {
+    fn record_transfer(&mut self, destination: &str, lamports: u64, via_cpi: bool) {
+        // Every System Program transfer is a candidate tip; only transfers to known landing
+        // service accounts count as tips, the rest are kept so unknown services can be found
+        if lamports > 0 {
+            self.transfers.push(TipTransfer {
+                recipient: destination.to_string(),
+                amount: lamports,
+                via_cpi: if via_cpi { 1 } else { 0 },
+                landing_service: identify_landing_service(destination),
+            });
+        }
+    }
+
+    fn record_compute_budget(&mut self, data: &[u8]) {
+        if let Some(limit) = decode_compute_unit_limit(data) {
+            self.compute_unit_limit = limit;
+        } else if let Some(price) = decode_compute_unit_price(data) {
+            self.compute_unit_price = price;
+        }
+    }
+
+    /// Classify the transaction from its transfers. Transfers to known landing service accounts
+    /// take precedence: the first one names the service and recipient, and the tip is the sum
+    /// paid to known accounts. Returns (landing_service, tip_recipient, tip_amount, tip_via_cpi)
+    fn classify_tip(&self) -> (String, String, u64, u8) {
+        let mut known = self.transfers.iter().filter(|t| t.landing_service.is_some());
+        match known.next() {
+            Some(first) => {
+                let tip_amount = first.amount + known.map(|t| t.amount).sum::<u64>();
+                let service = first.landing_service.clone().unwrap_or_default();
+                (service, first.recipient.clone(), tip_amount, first.via_cpi)
+            }
+            // Moving SOL to an account no landing service is known to use is an ordinary payment
+            None if !self.transfers.is_empty() => (PLAIN_TRANSFER.to_string(), String::new(), 0, 0),
+            None => (NO_TIP.to_string(), String::new(), 0, 0),
+        }
+    }
+}
+
+/// Lamports moved by a System Program transfer, decoded from the raw instruction data
+pub fn decode_system_transfer(data: &[u8]) -> Option<u64> {
+    if data.len() != 12 || data[..4] != SYSTEM_TRANSFER_TAG.to_le_bytes() {
+        return None;
+    }
+    data[4..12].try_into().ok().map(u64::from_le_bytes)
+}
+
+/// Compute unit limit requested by a ComputeBudget SetComputeUnitLimit instruction
+pub fn decode_compute_unit_limit(data: &[u8]) -> Option<u32> {
+    match data.split_first() {
+        Some((&SET_COMPUTE_UNIT_LIMIT_TAG, limit)) => limit.try_into().ok().map(u32::from_le_bytes),
+        _ => None,
+    }
+}
+
+/// Micro-lamports per compute unit set by a ComputeBudget SetComputeUnitPrice instruction
+pub fn decode_compute_unit_price(data: &[u8]) -> Option<u64> {
+    match data.split_first() {
+        Some((&SET_COMPUTE_UNIT_PRICE_TAG, price)) => price.try_into().ok().map(u64::from_le_bytes),
+        _ => None,
+    }
+}
+
+/// Loaded lookup table addresses from the transaction meta, writable first then readonly
+fn loaded_addresses(meta: Option<&UiTransactionStatusMeta>) -> impl Iterator<Item = &String> {
+    let loaded = meta.and_then(|m| Option::<&UiLoadedAddresses>::from(m.loaded_addresses.as_ref()));
+    loaded.into_iter().flat_map(|l| l.writable.iter().chain(l.readonly.iter()))
+}
+
+/// Account keys in the order instruction indices refer to them: the static message keys, then the
+/// writable and readonly addresses loaded from lookup tables (v0 transactions)
+pub fn resolve_account_keys(message: &UiMessage, meta: Option<&UiTransactionStatusMeta>) -> Vec<String> {
+    match message {
+        // jsonParsed messages already list lookup table accounts after the static keys
+        UiMessage::Parsed(parsed_message) => parsed_message.account_keys.iter().map(|a| a.pubkey.clone()).collect(),
+        UiMessage::Raw(raw_message) => {
+            let mut account_keys = raw_message.account_keys.clone();
+            account_keys.extend(loaded_addresses(meta).cloned());
+            account_keys
+        }
+    }
+}
+
+/// Resolve the program of a compiled instruction and record it if it is a System transfer or a
+/// compute budget setting. Compute budget instructions only take effect at the top level.
+fn scan_compiled_instruction(
+    account_keys: &[String],
+    program_id_index: u8,
+    accounts: &[u8],
+    data: &[u8],
+    scan: &mut InstructionScan,
+    via_cpi: bool,
+) -> Option<String> {
+    let program_id = account_keys.get(program_id_index as usize)?;
+    if program_id == SYSTEM_PROGRAM_ID {
+        // Transfer accounts are [source, destination]
+        let destination = accounts.get(1).and_then(|&idx| account_keys.get(idx as usize));
+        if let (Some(destination), Some(lamports)) = (destination, decode_system_transfer(data)) {
+            scan.record_transfer(destination, lamports, via_cpi);
+        }
+    } else if program_id == COMPUTE_BUDGET_PROGRAM_ID && !via_cpi {
+        scan.record_compute_budget(data);
+    }
+    Some(program_id.clone())
+}
+
+/// Resolve the program of an instruction as it appears in a JSON message or in the inner
+/// instructions of the transaction meta, recording tips and compute budget settings
+fn scan_ui_instruction(
+    inst: &UiInstruction,
+    account_keys: &[String],
+    scan: &mut InstructionScan,
+    via_cpi: bool,
+) -> Option<String> {
+    match inst {
+        UiInstruction::Parsed(parsed_instruction) => {
+            match parsed_instruction {
+                UiParsedInstruction::Parsed(instruction_parsed) => {
+                    // Check if this is a System Program transfer to a known tip account
+                    if instruction_parsed.program_id == SYSTEM_PROGRAM_ID {
+                        // The parsed field is already a Value, try to extract transfer info
+                        if let Some(parsed_data) = instruction_parsed.parsed.as_object() {
+                            // Check if it's a transfer instruction
+                            if let Some(inst_type) = parsed_data.get("type").and_then(|v| v.as_str()) {
+                                if inst_type == "transfer" {
+                                    // Extract destination address and amount
+                                    if let Some(info) = parsed_data.get("info").and_then(|v| v.as_object()) {
+                                        if let Some(destination) = info.get("destination").and_then(|v| v.as_str()) {
+                                            // Extract tip amount (lamports)
+                                            if let Some(lamports) = info.get("lamports").and_then(|v| v.as_u64()) {
+                                                scan.record_transfer(destination, lamports, via_cpi);
                                             }
-                                            instruction_parsed.program_id.clone()
                                         }
-                                        UiParsedInstruction::PartiallyDecoded(instruction_partial) => instruction_partial.program_id.clone(),
                                     }
                                 }
-                                UiInstruction::Compiled(compiled_instruction) => {
-                                    format!("Program ID Index: {}", compiled_instruction.program_id_index)
-                                }
                             }
-                        }).collect()
+                        }
                     }
-                    UiMessage::Raw(raw_message) => {
-                        raw_message.instructions.iter().map(|inst| {
-                            format!("Program ID Index: {}", inst.program_id_index)
-                        }).collect()
-                    }
-                };
-                
-                let has_compute_budget = program_ids.iter().any(|p| p.contains("ComputeBudget"));
-                let is_vote = program_ids.iter().any(|p| p.contains("Vote111111111111111111111111111111111111111"));
-                let is_system = program_ids.iter().any(|p| p == "11111111111111111111111111111111");
-                
-                // Set landing_service based on tip status
-                // If we have a tip but no known service, mark as unknown
-                if !tip_recipient.is_empty() && landing_service_found.is_empty() {
-                    landing_service_found = format!("Unknown: {}", tip_recipient);
+                    Some(instruction_parsed.program_id.clone())
                 }
-                // If we have no tip at all, mark as "No Tip"
-                if tip_recipient.is_empty() && landing_service_found.is_empty() {
-                    landing_service_found = "No Tip".to_string();
+                UiParsedInstruction::PartiallyDecoded(instruction_partial) => {
+                    // ComputeBudget has no jsonParsed parser, so its data stays base58
+                    if instruction_partial.program_id == COMPUTE_BUDGET_PROGRAM_ID && !via_cpi {
+                        let data = bs58::decode(&instruction_partial.data).into_vec().unwrap_or_default();
+                        scan.record_compute_budget(&data);
+                    }
+                    Some(instruction_partial.program_id.clone())
                 }
-                
-                let fee = transaction_with_meta.meta.as_ref().map(|m| m.fee).unwrap_or(0);
-                let failed = transaction_with_meta.meta.as_ref().map(|m| m.err.is_some()).unwrap_or(false);
-                
-                (recent_blockhash, fee, failed, has_compute_budget, is_vote, is_system, program_ids, landing_service_found, tip_recipient, tip_amount)
             }
-            _ => {
-                // Binary transaction - minimal data
-                let fee = transaction_with_meta.meta.as_ref().map(|m| m.fee).unwrap_or(0);
-                let failed = transaction_with_meta.meta.as_ref().map(|m| m.err.is_some()).unwrap_or(false);
-                (String::new(), fee, failed, false, false, false, Vec::new(), "No Tip".to_string(), String::new(), 0)
-            }
-        };
+        }
+        UiInstruction::Compiled(compiled_instruction) => {
+            let data = bs58::decode(&compiled_instruction.data).into_vec().unwrap_or_default();
+            scan_compiled_instruction(account_keys, compiled_instruction.program_id_index, &compiled_instruction.accounts, &data, scan, via_cpi)
+        }
+    }
+}
+
+/// Record tips paid by programs the transaction invoked (CPI), found in the inner instructions
+fn scan_inner_instructions(meta: Option<&UiTransactionStatusMeta>, account_keys: &[String], scan: &mut InstructionScan) {
+    let inner_instructions = meta.and_then(|m| Option::<&Vec<UiInnerInstructions>>::from(m.inner_instructions.as_ref()));
+    for inner in inner_instructions.into_iter().flatten() {
+        for inst in &inner.instructions {
+            scan_ui_instruction(inst, account_keys, scan, true);
+        }
+    }
+}
+
+fn scan_json_transaction(
+    transaction_json: &UiTransaction,
+    meta: Option<&UiTransactionStatusMeta>,
+    scan: &mut InstructionScan,
+) -> ScannedMessage {
+    let message = &transaction_json.message;
+    let signature = transaction_json.signatures.first().cloned().unwrap_or_default();
+
+    let recent_blockhash = match message {
+        UiMessage::Parsed(parsed_message) => parsed_message.recent_blockhash.clone(),
+        UiMessage::Raw(raw_message) => raw_message.recent_blockhash.clone(),
+    };
+
+    let account_keys = resolve_account_keys(message, meta);
+
+    let program_ids: Vec<String> = match message {
+        UiMessage::Parsed(parsed_message) => {
+            parsed_message.instructions.iter().filter_map(|inst| {
+                scan_ui_instruction(inst, &account_keys, scan, false)
+            }).collect()
+        }
+        UiMessage::Raw(raw_message) => {
+            raw_message.instructions.iter().filter_map(|inst| {
+                let data = bs58::decode(&inst.data).into_vec().unwrap_or_default();
+                scan_compiled_instruction(&account_keys, inst.program_id_index, &inst.accounts, &data, scan, false)
+            }).collect()
+        }
+    };
+    scan_inner_instructions(meta, &account_keys, scan);
+
+    ScannedMessage {
+        signature,
+        signature_count: transaction_json.signatures.len(),
+        // The fee payer is always the first account
+        fee_payer: account_keys.first().cloned().unwrap_or_default(),
+        recent_blockhash,
+        program_ids,
+    }
+}
+
+fn scan_versioned_transaction(
+    transaction: &VersionedTransaction,
+    meta: Option<&UiTransactionStatusMeta>,
+    scan: &mut InstructionScan,
+) -> ScannedMessage {
+    let message = &transaction.message;
+    let signature = transaction.signatures.first().map(|s| s.to_string()).unwrap_or_default();
+
+    let mut account_keys: Vec<String> = message.static_account_keys().iter().map(|k| k.to_string()).collect();
+    account_keys.extend(loaded_addresses(meta).cloned());
+
+    let program_ids = message
+        .instructions()
+        .iter()
+        .filter_map(|inst| scan_compiled_instruction(&account_keys, inst.program_id_index, &inst.accounts, &inst.data, scan, false))
+        .collect();
+    scan_inner_instructions(meta, &account_keys, scan);
+
+    ScannedMessage {
+        signature,
+        signature_count: transaction.signatures.len(),
+        fee_payer: account_keys.first().cloned().unwrap_or_default(),
+        recent_blockhash: message.recent_blockhash().to_string(),
+        program_ids,
+    }
+}
+
+pub fn parse_transactions(
+    block_transactions: &[EncodedTransactionWithStatusMeta],
+    slot: u64,
+) -> Vec<Transaction> {
+    let mut transactions = Vec::new();
+    
+    for (idx, transaction_with_meta) in block_transactions.iter().enumerate() {
+        let position = (idx + 1) as u32;
+        let meta = transaction_with_meta.meta.as_ref();
+        let mut scan = InstructionScan::default();
         
-        let signature = match &transaction_with_meta.transaction {
-            EncodedTransaction::Json(transaction_json) => {
-                if !transaction_json.signatures.is_empty() {
-                    transaction_json.signatures[0].to_string()
-                } else {
-                    String::new()
+        let scanned = match &transaction_with_meta.transaction {
+            EncodedTransaction::Json(transaction_json) => scan_json_transaction(transaction_json, meta, &mut scan),
+            encoded => match encoded.decode() {
+                Some(transaction) => scan_versioned_transaction(&transaction, meta, &mut scan),
+                None => {
+                    eprintln!("WARNING: Could not decode transaction {} in slot {}", position, slot);
+                    ScannedMessage::default()
                 }
-            }
-            _ => String::new(),
+            },
         };
         
-        transactions.push(Transaction {
-            slot,
-            position,
-            signature,
-            recent_blockhash,
-            fee,
-            failed: if failed { 1 } else { 0 },
-            has_compute_budget: if has_compute_budget { 1 } else { 0 },
-            is_vote: if is_vote { 1 } else { 0 },
-            is_system: if is_system { 1 } else { 0 },
-            program_ids,
-            landing_service,
-            tip_recipient,
-            tip_amount,
-        });
+        transactions.push(build_transaction(slot, position, scanned, scan, meta));
     }
     
     transactions
 }
 
+/// Parse transactions that arrive already decoded rather than as a `getBlock` response, such as
+/// those streamed over Geyser, in block order. A transaction that could not be decoded is None.
+#[cfg(feature = "geyser")]
+pub fn parse_decoded_transactions(
+    block_transactions: &[(Option<VersionedTransaction>, Option<UiTransactionStatusMeta>)],
+    slot: u64,
+) -> Vec<Transaction> {
+    block_transactions
+        .iter()
+        .enumerate()
+        .map(|(idx, (transaction, meta))| {
+            let position = (idx + 1) as u32;
+            let mut scan = InstructionScan::default();
+            let scanned = match transaction {
+                Some(transaction) => scan_versioned_transaction(transaction, meta.as_ref(), &mut scan),
+                None => {
+                    eprintln!("WARNING: Could not decode transaction {} in slot {}", position, slot);
+                    ScannedMessage::default()
+                }
+            };
+            build_transaction(slot, position, scanned, scan, meta.as_ref())
+        })
+        .collect()
+}
+
+fn build_transaction(
+    slot: u64,
+    position: u32,
+    scanned: ScannedMessage,
+    scan: InstructionScan,
+    meta: Option<&UiTransactionStatusMeta>,
+) -> Transaction {
+    let program_ids = scanned.program_ids;
+    let has_compute_budget = program_ids.iter().any(|p| p.contains("ComputeBudget"));
+    let is_vote = program_ids.iter().any(|p| p.contains("Vote111111111111111111111111111111111111111"));
+    let is_system = program_ids.iter().any(|p| p == SYSTEM_PROGRAM_ID);
+    
+    let (landing_service, tip_recipient, tip_amount, tip_via_cpi) = scan.classify_tip();
+    
+    let fee = meta.map(|m| m.fee).unwrap_or(0);
+    let failed = meta.map(|m| m.err.is_some()).unwrap_or(false);
+    // Everything charged above the per-signature base fee is the prioritization fee
+    let base_fee = (scanned.signature_count as u64 * LAMPORTS_PER_SIGNATURE).min(fee);
+    let priority_fee = fee - base_fee;
+    let compute_units_consumed = meta
+        .and_then(|m| Option::<u64>::from(m.compute_units_consumed.clone()))
+        .unwrap_or(0);
+    
+    Transaction {
+        slot,
+        position,
+        signature: scanned.signature,
+        fee_payer: scanned.fee_payer,
+        recent_blockhash: scanned.recent_blockhash,
+        fee,
+        failed: if failed { 1 } else { 0 },
+        has_compute_budget: if has_compute_budget { 1 } else { 0 },
+        is_vote: if is_vote { 1 } else { 0 },
+        is_system: if is_system { 1 } else { 0 },
+        program_ids,
+        landing_service,
+        tip_recipient,
+        tip_amount,
+        tip_via_cpi,
+        tip_transfers: scan.transfers,
+        compute_units_consumed,
+        base_fee,
+        priority_fee,
+        compute_unit_price: scan.compute_unit_price,
+        compute_unit_limit: scan.compute_unit_limit,
+    }
+}
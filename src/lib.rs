@@ -1,15 +1,36 @@
 pub mod analyzer {
     pub mod bundling;
+    pub mod columns;
     pub mod fee_by_type;
     pub mod fee_landscape;
     pub mod landing_services;
     pub mod program_fee;
+    pub mod registry;
+    pub mod tip_candidates;
+    pub mod transactions;
     pub mod types;
     pub mod utils;
 }
 
 pub mod core {
+    pub mod backfill;
     pub mod block_analyzer;
     pub mod block_fetcher;
+    pub mod config;
+    pub mod failed_slots;
+    pub mod gap_audit;
+    pub mod ingest_error;
+    #[cfg(feature = "geyser")]
+    pub mod geyser;
+    #[cfg(feature = "geyser")]
+    pub mod geyser_proto;
+    pub mod leader_schedule;
+    pub mod pipeline;
+    pub mod replay;
+    pub mod retry;
+    pub mod rpc_pool;
+    pub mod sink;
+    pub mod slot_status;
+    pub mod slot_subscription;
     pub mod transaction_parser;
 }
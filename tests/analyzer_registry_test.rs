@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use solana_block_fetcher::analyzer::registry::{AnalyzerRegistry, BlockContext};
+use solana_block_fetcher::analyzer::types::Transaction;
+use solana_block_fetcher::core::block_analyzer::build_registry;
+use solana_block_fetcher::core::config::AnalysisConfig;
+
+#[test]
+fn test_builtin_analyzers_have_distinct_tables_and_schemas() {
+    let registry = AnalyzerRegistry::builtin();
+
+    let tables: HashSet<&str> = registry.all().map(|a| a.table()).collect();
+    assert_eq!(tables.len(), registry.names().len());
+    for analyzer in registry.all() {
+        // execute_schema_file rewrites this line to point at the configured database
+        assert!(analyzer.schema().contains("CREATE TABLE IF NOT EXISTS"), "{} has no CREATE TABLE", analyzer.name());
+        // Processing a slot again must replace its rows rather than add to the totals
+        assert!(analyzer.schema().contains("ReplacingMergeTree"), "{} does not deduplicate rows", analyzer.name());
+    }
+
+    // The per-transaction table is opt-in
+    let enabled: Vec<&str> = registry.enabled().map(|a| a.name()).collect();
+    assert_eq!(enabled, vec!["bundling", "fee_landscape", "program_fee", "fee_by_type", "tip_candidates"]);
+}
+
+#[test]
+fn test_analyzers_produce_rows_for_block() {
+    let transactions = vec![
+        Transaction { slot: 1000, position: 1, fee: 5000, ..Default::default() },
+        Transaction { slot: 1000, position: 2, fee: 7000, ..Default::default() },
+    ];
+    let block = BlockContext {
+        slot: 1000,
+        blockhash: "hash",
+        block_time: 1234567890,
+        transactions: &transactions,
+        validator_key: Some("validator1"),
+    };
+    let registry = AnalyzerRegistry::builtin();
+
+    let row_counts: Vec<(&str, usize)> = registry.all().map(|a| (a.name(), a.analyze(&block).len())).collect();
+    // Sinks that need a schema get it from every analyzer's row type
+    for analyzer in registry.all() {
+        assert!(analyzer.analyze(&block).columns().is_ok(), "{} rows have no columns", analyzer.name());
+    }
+
+    assert_eq!(
+        row_counts,
+        vec![
+            ("bundling", 1),
+            ("fee_landscape", 1),
+            ("program_fee", 0),
+            ("fee_by_type", 1),
+            // No unknown tip recipients were seen
+            ("tip_candidates", 0),
+            ("transactions", 2),
+        ]
+    );
+}
+
+#[test]
+fn test_registry_follows_config() {
+    let analysis = AnalysisConfig {
+        enabled_analyzers: vec!["transactions".to_string()],
+        disabled_analyzers: vec!["fee_by_type".to_string(), "tip_candidates".to_string()],
+        ..Default::default()
+    };
+
+    let registry = build_registry(&analysis).unwrap();
+
+    let enabled: Vec<&str> = registry.enabled().map(|a| a.name()).collect();
+    assert_eq!(enabled, vec!["bundling", "fee_landscape", "program_fee", "transactions"]);
+    // Disabled analyzers keep their tables
+    assert_eq!(registry.all().count(), 6);
+
+    // store_transactions is shorthand for enabling the transactions analyzer
+    let registry = build_registry(&AnalysisConfig { store_transactions: true, ..Default::default() }).unwrap();
+    assert!(registry.enabled().any(|a| a.name() == "transactions"));
+}
+
+#[test]
+fn test_registry_rejects_unknown_or_conflicting_names() {
+    let unknown = AnalysisConfig { disabled_analyzers: vec!["mev".to_string()], ..Default::default() };
+    let err = build_registry(&unknown).err().unwrap();
+    assert!(err.contains("unknown analyzer 'mev'"));
+
+    let conflicting = AnalysisConfig {
+        enabled_analyzers: vec!["bundling".to_string()],
+        disabled_analyzers: vec!["bundling".to_string()],
+        ..Default::default()
+    };
+    assert!(build_registry(&conflicting).is_err());
+}
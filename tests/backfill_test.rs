@@ -0,0 +1,26 @@
+use solana_block_fetcher::core::backfill::resume_slot;
+
+#[test]
+fn test_resume_slot_fresh_range() {
+    // No progress recorded yet - start from the beginning of the range
+    assert_eq!(resume_slot(390804664, 390806009, None), Some(390804664));
+}
+
+#[test]
+fn test_resume_slot_partial_progress() {
+    // Continue from the slot after the last one handled
+    assert_eq!(resume_slot(390804664, 390806009, Some(390805000)), Some(390805001));
+}
+
+#[test]
+fn test_resume_slot_complete_range() {
+    // Last slot of the range handled - nothing left to do
+    assert_eq!(resume_slot(390804664, 390806009, Some(390806009)), None);
+}
+
+#[test]
+fn test_resume_slot_single_slot_range() {
+    // Edge case: range containing a single slot
+    assert_eq!(resume_slot(1000, 1000, None), Some(1000));
+    assert_eq!(resume_slot(1000, 1000, Some(1000)), None);
+}
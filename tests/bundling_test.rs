@@ -20,6 +20,7 @@ fn test_bundle_detection_same_tip_recipient() {
             landing_service: String::new(),
             tip_recipient: jito_tip.clone(),
             tip_amount: 10000,
+            ..Default::default()
         },
         Transaction {
             slot: 1000,
@@ -35,6 +36,7 @@ fn test_bundle_detection_same_tip_recipient() {
             landing_service: String::new(),
             tip_recipient: jito_tip.clone(),
             tip_amount: 10000,
+            ..Default::default()
         },
         Transaction {
             slot: 1000,
@@ -50,10 +52,11 @@ fn test_bundle_detection_same_tip_recipient() {
             landing_service: String::new(),
             tip_recipient: jito_tip.clone(),
             tip_amount: 10000,
+            ..Default::default()
         },
     ];
     
-    let result = analyze_bundling(&transactions, 1000, "blockhash".to_string(), 1234567890, "validator1".to_string());
+    let result = analyze_bundling(&transactions, 1000, "blockhash".to_string(), 1234567890, Some("validator1".to_string()));
     
     // Should detect bundle of size 3
     assert_eq!(result.largest_bundle_size, 3);
@@ -77,6 +80,7 @@ fn test_no_bundles_no_tips() {
             landing_service: String::new(),
             tip_recipient: String::new(),
             tip_amount: 0,
+            ..Default::default()
         },
         Transaction {
             slot: 1000,
@@ -92,10 +96,11 @@ fn test_no_bundles_no_tips() {
             landing_service: String::new(),
             tip_recipient: String::new(),
             tip_amount: 0,
+            ..Default::default()
         },
     ];
     
-    let result = analyze_bundling(&transactions, 1000, "blockhash".to_string(), 1234567890, "validator1".to_string());
+    let result = analyze_bundling(&transactions, 1000, "blockhash".to_string(), 1234567890, Some("validator1".to_string()));
     
     // No bundles detected (need at least 2 transactions with tips)
     assert_eq!(result.largest_bundle_size, 0);
@@ -0,0 +1,23 @@
+//! Helpers shared by the integration tests. Each test crate only uses some of them.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+
+/// A JSON-RPC error response with `code`, as the RPC client returns it
+pub fn rpc_response_error(code: i64, message: &str) -> ClientError {
+    ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code,
+        message: message.to_string(),
+        data: RpcResponseErrorData::Empty,
+    })
+    .into()
+}
+
+/// An empty temporary directory for `name`, which must be unique across the tests
+pub fn output_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("solana_block_fetcher_test_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
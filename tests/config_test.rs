@@ -0,0 +1,148 @@
+use solana_block_fetcher::core::config::{
+    parse_commitment, parse_encoding, parse_sink, parse_subscription, redact_password, Config, ConfigArgs, SinkKind, SubscriptionKind,
+};
+use solana_transaction_status::UiTransactionEncoding;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+#[test]
+fn test_config_example_file_parses() {
+    // The shipped example must stay in sync with the config structs
+    let config = Config::from_toml_str(include_str!("../config.example.toml")).unwrap();
+
+    assert!(config.validate().is_ok());
+    assert_eq!(config.rpc.endpoints.len(), 2);
+    assert_eq!(config.clickhouse.database, "solana");
+}
+
+#[test]
+fn test_config_defaults_for_missing_sections() {
+    // Only ClickHouse given - everything else keeps its default
+    let config = Config::from_toml_str("[clickhouse]\nurl = \"http://clickhouse:8123\"\n").unwrap();
+
+    assert_eq!(config.clickhouse.url, "http://clickhouse:8123");
+    assert_eq!(config.clickhouse.user, "default");
+    assert_eq!(config.ingest.batch_size, 100);
+    assert_eq!(config.retry.max_attempts, 3);
+    // Stored transactions are kept unless a retention is configured
+    assert_eq!(config.analysis.transactions_retention_days, None);
+    assert_eq!(config.rpc.endpoints.len(), 1);
+    assert_eq!(config.commitment(), CommitmentConfig::finalized());
+}
+
+#[test]
+fn test_config_endpoints_sorted_by_priority() {
+    let config = Config::from_toml_str(
+        r#"
+        [[rpc.endpoints]]
+        url = "https://fallback.example"
+        priority = 5
+
+        [[rpc.endpoints]]
+        url = "https://primary.example"
+        priority = 1
+        "#,
+    )
+    .unwrap();
+
+    let urls: Vec<String> = config.sorted_endpoints().into_iter().map(|e| e.url).collect();
+    assert_eq!(urls, vec!["https://primary.example", "https://fallback.example"]);
+}
+
+#[test]
+fn test_config_rejects_unknown_fields() {
+    // Typos in the config file should be reported instead of silently ignored
+    assert!(Config::from_toml_str("[ingest]\nbatchsize = 10\n").is_err());
+}
+
+#[test]
+fn test_config_validate_rejects_empty_endpoints() {
+    let config = Config::from_toml_str("[rpc]\nendpoints = []\n").unwrap();
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_config_validate_rejects_zero_retention() {
+    let config = Config::from_toml_str("[analysis]
+transactions_retention_days = 0
+").unwrap();
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_parse_commitment_levels() {
+    assert_eq!(parse_commitment("confirmed").unwrap(), CommitmentConfig::confirmed());
+    assert_eq!(parse_commitment("finalized").unwrap(), CommitmentConfig::finalized());
+    // getBlock does not support processed commitment
+    assert!(parse_commitment("processed").is_err());
+    assert!(parse_commitment("fast").is_err());
+}
+
+#[test]
+fn test_parse_encoding() {
+    assert_eq!(parse_encoding("jsonParsed").unwrap(), UiTransactionEncoding::JsonParsed);
+    assert_eq!(parse_encoding("base64").unwrap(), UiTransactionEncoding::Base64);
+    // base58 is slow and size limited by the RPC
+    assert!(parse_encoding("base58").is_err());
+}
+
+#[test]
+fn test_parse_sink() {
+    assert_eq!(parse_sink("clickhouse").unwrap(), SinkKind::ClickHouse);
+    assert_eq!(parse_sink("parquet").unwrap(), SinkKind::Parquet);
+    assert_eq!(parse_sink("jsonl").unwrap(), SinkKind::Jsonl);
+    assert!(parse_sink("csv").is_err());
+    assert_eq!(Config::default().sink(), SinkKind::ClickHouse);
+}
+
+#[test]
+fn test_subscription_and_websocket_url() {
+    assert_eq!(parse_subscription("none").unwrap(), None);
+    assert_eq!(parse_subscription("block").unwrap(), Some(SubscriptionKind::Block));
+    assert!(parse_subscription("account").is_err());
+
+    // The PubSub URL is derived from the first RPC endpoint unless given
+    let mut config = Config::default();
+    assert_eq!(config.subscription(), None);
+    assert_eq!(config.websocket_url().as_deref(), Some("wss://api.mainnet-beta.solana.com"));
+    config.ingest.websocket_url = Some("ws://localhost:8900".to_string());
+    assert_eq!(config.websocket_url().as_deref(), Some("ws://localhost:8900"));
+}
+
+#[test]
+fn test_cli_overrides_replace_config_values() {
+    let mut config = Config::default();
+    let overrides = ConfigArgs {
+        rpc_urls: vec!["https://a.example".to_string(), "https://b.example".to_string()],
+        clickhouse_database: Some("research".to_string()),
+        batch_size: Some(25),
+        store_transactions: true,
+        geyser_endpoint: Some("https://geyser.example:443".to_string()),
+        retry_attempts: Some(5),
+        transactions_retention_days: Some(14),
+        ..Default::default()
+    };
+
+    overrides.apply(&mut config);
+
+    let endpoints = config.sorted_endpoints();
+    assert_eq!(endpoints[0].url, "https://a.example");
+    assert_eq!(endpoints[1].priority, 1);
+    assert_eq!(config.clickhouse.database, "research");
+    assert_eq!(config.ingest.batch_size, 25);
+    assert!(config.analysis.store_transactions);
+    assert_eq!(config.geyser.endpoint.as_deref(), Some("https://geyser.example:443"));
+    assert_eq!(config.geyser.x_token, None);
+    assert_eq!(config.retry.max_attempts, 5);
+    assert_eq!(config.analysis.transactions_retention_days, Some(14));
+    // Values not overridden are left untouched
+    assert_eq!(config.ingest.concurrency, 8);
+}
+
+#[test]
+fn test_redact_password_hides_the_value() {
+    assert_eq!(redact_password("solana123"), "********");
+    // Edge case: an empty password is shown as unset rather than masked
+    assert_eq!(redact_password(""), "(none)");
+}
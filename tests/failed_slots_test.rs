@@ -0,0 +1,89 @@
+use std::sync::{Arc, Mutex};
+use futures::future::BoxFuture;
+use solana_block_fetcher::analyzer::registry::{AnalyzerRegistry, RowBatch};
+use solana_block_fetcher::core::block_fetcher::BlockFetcher;
+use solana_block_fetcher::core::config::RetryConfig;
+use solana_block_fetcher::core::failed_slots::{run_retry_failed, FailedSlotRow};
+use solana_block_fetcher::core::retry::RetryPolicy;
+use solana_block_fetcher::core::rpc_pool::RpcPool;
+use solana_block_fetcher::core::sink::{Sink, SinkError};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_transaction_status::UiTransactionEncoding;
+
+/// Serves a fixed list of failed slots and keeps every failed_slots row written
+struct MemorySink {
+    failed: Vec<FailedSlotRow>,
+    written: Mutex<Vec<serde_json::Value>>,
+}
+
+impl MemorySink {
+    fn new(slots: &[u64]) -> Self {
+        let failed = slots
+            .iter()
+            .map(|&slot| FailedSlotRow { slot, error_kind: "rpc".to_string(), error: "timed out".to_string(), attempts: 3, resolved: 0 })
+            .collect();
+        MemorySink { failed, written: Mutex::new(Vec::new()) }
+    }
+}
+
+impl Sink for MemorySink {
+    fn write<'a>(&'a self, table: &'a str, rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            if table == "failed_slots" {
+                self.written.lock().unwrap().extend(rows.to_json()?);
+            }
+            Ok(())
+        })
+    }
+
+    fn failed_slots(&self) -> BoxFuture<'_, Result<Vec<FailedSlotRow>, SinkError>> {
+        let failed = self.failed.clone();
+        Box::pin(async move { Ok(failed) })
+    }
+}
+
+fn fetcher(mock: &str) -> Arc<BlockFetcher> {
+    let client = RpcClient::new_mock(mock.to_string());
+    let rpc_pool = RpcPool::from_clients(vec![(mock.to_string(), 0, client)], CommitmentConfig::finalized());
+    let policy = RetryPolicy::new(&RetryConfig { max_attempts: 2, base_delay_ms: 1, max_delay_ms: 1, rate_limit_delay_ms: 1 });
+    Arc::new(BlockFetcher::new(Arc::new(rpc_pool), UiTransactionEncoding::Base64).with_retry_policy(policy))
+}
+
+#[tokio::test]
+async fn test_retry_failed_marks_recovered_slots_resolved() {
+    let sink = MemorySink::new(&[5, 9]);
+
+    run_retry_failed(&sink, fetcher("succeeds"), 2, &AnalyzerRegistry::builtin()).await.unwrap();
+
+    let written = sink.written.lock().unwrap();
+    assert_eq!(written.len(), 2);
+    assert_eq!(written[0]["slot"], 5);
+    assert_eq!(written[0]["resolved"], 1);
+    assert_eq!(written[1]["slot"], 9);
+    assert_eq!(written[1]["attempts"], 4);
+}
+
+#[tokio::test]
+async fn test_retry_failed_keeps_slots_that_fail_again() {
+    // The "fails" mock answers getBlock with null, which cannot be decoded
+    let sink = MemorySink::new(&[5]);
+
+    run_retry_failed(&sink, fetcher("fails"), 2, &AnalyzerRegistry::builtin()).await.unwrap();
+
+    let written = sink.written.lock().unwrap();
+    assert_eq!(written.len(), 1);
+    assert_eq!(written[0]["resolved"], 0);
+    assert_eq!(written[0]["error_kind"], "rpc");
+    // Three earlier attempts plus both attempts of this run
+    assert_eq!(written[0]["attempts"], 5);
+}
+
+#[tokio::test]
+async fn test_retry_failed_with_nothing_to_retry() {
+    let sink = MemorySink::new(&[]);
+
+    run_retry_failed(&sink, fetcher("succeeds"), 2, &AnalyzerRegistry::builtin()).await.unwrap();
+
+    assert!(sink.written.lock().unwrap().is_empty());
+}
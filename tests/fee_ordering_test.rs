@@ -5,10 +5,10 @@ use solana_block_fetcher::analyzer::types::Transaction;
 fn test_fee_avg_calculation() {
     // Test fee average calculation
     let transactions = vec![
-        Transaction { slot: 1000, position: 1, signature: "sig1".to_string(), recent_blockhash: "bh1".to_string(), fee: 10000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
-        Transaction { slot: 1000, position: 2, signature: "sig2".to_string(), recent_blockhash: "bh2".to_string(), fee: 8000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
-        Transaction { slot: 1000, position: 3, signature: "sig3".to_string(), recent_blockhash: "bh3".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
-        Transaction { slot: 1000, position: 4, signature: "sig4".to_string(), recent_blockhash: "bh4".to_string(), fee: 2000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
+        Transaction { slot: 1000, position: 1, signature: "sig1".to_string(), recent_blockhash: "bh1".to_string(), fee: 10000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
+        Transaction { slot: 1000, position: 2, signature: "sig2".to_string(), recent_blockhash: "bh2".to_string(), fee: 8000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
+        Transaction { slot: 1000, position: 3, signature: "sig3".to_string(), recent_blockhash: "bh3".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
+        Transaction { slot: 1000, position: 4, signature: "sig4".to_string(), recent_blockhash: "bh4".to_string(), fee: 2000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
     ];
     
     let result = analyze_fee_landscape(&transactions, 1000, 1234567890);
@@ -20,7 +20,7 @@ fn test_fee_avg_calculation() {
 fn test_fee_avg_single_transaction() {
     // Edge case: single transaction
     let transactions = vec![
-        Transaction { slot: 1000, position: 1, signature: "sig1".to_string(), recent_blockhash: "bh1".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
+        Transaction { slot: 1000, position: 1, signature: "sig1".to_string(), recent_blockhash: "bh1".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
     ];
     
     let result = analyze_fee_landscape(&transactions, 1000, 1234567890);
@@ -32,10 +32,10 @@ fn test_fee_avg_single_transaction() {
 fn test_fee_ordering_compute_budget_percentage() {
     // Test compute budget usage calculation
     let transactions = vec![
-        Transaction { slot: 1000, position: 1, signature: "sig1".to_string(), recent_blockhash: "bh1".to_string(), fee: 5000, failed: 0, has_compute_budget: 1, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
-        Transaction { slot: 1000, position: 2, signature: "sig2".to_string(), recent_blockhash: "bh2".to_string(), fee: 5000, failed: 0, has_compute_budget: 1, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
-        Transaction { slot: 1000, position: 3, signature: "sig3".to_string(), recent_blockhash: "bh3".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
-        Transaction { slot: 1000, position: 4, signature: "sig4".to_string(), recent_blockhash: "bh4".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0 },
+        Transaction { slot: 1000, position: 1, signature: "sig1".to_string(), recent_blockhash: "bh1".to_string(), fee: 5000, failed: 0, has_compute_budget: 1, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
+        Transaction { slot: 1000, position: 2, signature: "sig2".to_string(), recent_blockhash: "bh2".to_string(), fee: 5000, failed: 0, has_compute_budget: 1, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
+        Transaction { slot: 1000, position: 3, signature: "sig3".to_string(), recent_blockhash: "bh3".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
+        Transaction { slot: 1000, position: 4, signature: "sig4".to_string(), recent_blockhash: "bh4".to_string(), fee: 5000, failed: 0, has_compute_budget: 0, is_vote: 0, is_system: 0, program_ids: vec![], landing_service: String::new(), tip_recipient: String::new(), tip_amount: 0, ..Default::default() },
     ];
     
     let result = analyze_fee_landscape(&transactions, 1000, 1234567890);
@@ -59,3 +59,38 @@ fn test_fee_ordering_empty_transactions() {
     assert!(result.compute_budget_percent.is_nan() || result.compute_budget_percent.is_infinite());
 }
 
+
+#[test]
+fn test_compute_unit_aggregates() {
+    let transactions: Vec<Transaction> = (1..=10)
+        .map(|i| Transaction { slot: 1000, position: i, compute_units_consumed: i as u64 * 100_000, ..Default::default() })
+        .collect();
+
+    let result = analyze_fee_landscape(&transactions, 1000, 1234567890);
+
+    assert_eq!(result.total_compute_units, 5_500_000);
+    assert_eq!(result.compute_units_p50, 600_000);
+    assert_eq!(result.compute_units_p90, 1_000_000);
+    assert_eq!(result.compute_units_p99, 1_000_000);
+    // 5.5M of the 48M block limit
+    assert!((result.block_cu_utilization_percent - 11.458).abs() < 0.01);
+}
+
+#[test]
+fn test_fee_breakdown_aggregates() {
+    let transactions = vec![
+        Transaction { slot: 1000, position: 1, fee: 5000, base_fee: 5000, ..Default::default() },
+        Transaction { slot: 1000, position: 2, fee: 25000, base_fee: 5000, priority_fee: 20000, compute_unit_price: 100_000, compute_unit_limit: 200_000, tip_amount: 1000, ..Default::default() },
+        Transaction { slot: 1000, position: 3, fee: 15000, base_fee: 10000, priority_fee: 5000, compute_unit_price: 25_000, ..Default::default() },
+    ];
+
+    let result = analyze_fee_landscape(&transactions, 1000, 1234567890);
+
+    assert_eq!(result.total_base_fee, 20000);
+    assert_eq!(result.total_priority_fee, 25000);
+    assert_eq!(result.total_tip, 1000);
+    // Transactions without a compute unit price are left out of the price percentiles
+    assert_eq!(result.compute_unit_price_p50, 100_000);
+    assert_eq!(result.compute_unit_price_p90, 100_000);
+    assert_eq!(result.compute_unit_limit_avg, 200_000.0);
+}
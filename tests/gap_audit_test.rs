@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use futures::future::BoxFuture;
+use serde_json::json;
+use solana_block_fetcher::analyzer::registry::{AnalyzerRegistry, RowBatch};
+use solana_block_fetcher::core::block_fetcher::BlockFetcher;
+use solana_block_fetcher::core::gap_audit::{find_gaps, run_gap_audit, GapReport};
+use solana_block_fetcher::core::rpc_pool::RpcPool;
+use solana_block_fetcher::core::sink::{Sink, SinkError};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_transaction_status::UiTransactionEncoding;
+
+/// Keeps the stored slots in memory and records the slot of every block and slot status written
+struct MemorySink {
+    stored: Vec<u64>,
+    written: Mutex<Vec<u64>>,
+    statuses: Mutex<Vec<(u64, String)>>,
+}
+
+impl Sink for MemorySink {
+    fn write<'a>(&'a self, table: &'a str, rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            let rows = rows.to_json()?;
+            if table == "blocks" {
+                self.written.lock().unwrap().extend(rows.iter().map(|row| row["slot"].as_u64().unwrap()));
+            } else if table == "slot_status" {
+                let statuses = rows.iter().map(|row| (row["slot"].as_u64().unwrap(), row["status"].as_str().unwrap().to_string()));
+                self.statuses.lock().unwrap().extend(statuses);
+            }
+            Ok(())
+        })
+    }
+
+    fn stored_slots(&self, start_slot: u64, end_slot: u64) -> BoxFuture<'_, Result<Vec<u64>, SinkError>> {
+        let slots = self.stored.iter().copied().filter(|slot| (start_slot..=end_slot).contains(slot)).collect();
+        Box::pin(async move { Ok(slots) })
+    }
+}
+
+#[test]
+fn test_find_gaps_separates_skipped_slots_from_missing_blocks() {
+    // Slots 12 and 15 have no block on chain; 13 has one that was never stored
+    let report = find_gaps(10, 16, &[10, 11, 13, 14, 16], &[10, 11, 14, 16]);
+
+    assert_eq!(report.missing, vec![13]);
+    assert_eq!(report.skipped, 2);
+    assert!(report.not_on_chain.is_empty());
+}
+
+#[test]
+fn test_find_gaps_reports_stored_blocks_not_on_chain() {
+    // Edge case: slot 21 was stored but the chain lists no block for it
+    let report = find_gaps(20, 22, &[20, 22], &[20, 21, 22, 30]);
+
+    assert_eq!(report, GapReport { missing: Vec::new(), skipped: 1, not_on_chain: vec![21] });
+}
+
+/// The mock RPC lists blocks for slots 1, 2 and 3, with the chain tip at `current_slot`
+fn fetcher(current_slot: u64) -> Arc<BlockFetcher> {
+    let mocks = HashMap::from([(RpcRequest::GetSlot, json!(current_slot))]);
+    let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+    let rpc_pool = RpcPool::from_clients(vec![("succeeds".to_string(), 0, client)], CommitmentConfig::finalized());
+    Arc::new(BlockFetcher::new(Arc::new(rpc_pool), UiTransactionEncoding::Base64))
+}
+
+fn memory_sink(stored: Vec<u64>) -> MemorySink {
+    MemorySink { stored, written: Mutex::new(Vec::new()), statuses: Mutex::new(Vec::new()) }
+}
+
+#[tokio::test]
+async fn test_gap_audit_refetches_missing_blocks() {
+    let sink = memory_sink(vec![1, 3]);
+
+    let report = run_gap_audit(&sink, fetcher(10), 1, 4, 2, &AnalyzerRegistry::builtin(), true).await.unwrap();
+    assert_eq!(report.missing, vec![2]);
+    assert_eq!(report.skipped, 1);
+    assert!(sink.written.lock().unwrap().is_empty());
+    assert!(sink.statuses.lock().unwrap().is_empty());
+
+    run_gap_audit(&sink, fetcher(10), 1, 4, 2, &AnalyzerRegistry::builtin(), false).await.unwrap();
+    assert_eq!(*sink.written.lock().unwrap(), vec![2]);
+    // The slot without a block on chain is recorded as skipped
+    assert_eq!(*sink.statuses.lock().unwrap(), vec![(4, "skipped".to_string()), (2, "produced".to_string())]);
+}
+
+#[tokio::test]
+async fn test_gap_audit_stops_at_the_chain_tip() {
+    let sink = memory_sink(vec![1, 2]);
+
+    // Edge case: slots 4 and 5 are not produced yet, so they are neither skipped nor missing
+    let report = run_gap_audit(&sink, fetcher(3), 1, 5, 2, &AnalyzerRegistry::builtin(), false).await.unwrap();
+    assert_eq!(report, GapReport { missing: vec![3], skipped: 0, not_on_chain: Vec::new() });
+    assert_eq!(*sink.statuses.lock().unwrap(), vec![(3, "produced".to_string())]);
+
+    // A range that starts past the tip is refused
+    assert!(run_gap_audit(&sink, fetcher(3), 4, 5, 2, &AnalyzerRegistry::builtin(), true).await.is_err());
+}
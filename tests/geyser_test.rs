@@ -0,0 +1,243 @@
+#![cfg(feature = "geyser")]
+
+use std::convert::Infallible;
+use std::str::FromStr;
+use solana_block_fetcher::core::geyser::{parse_geyser_block, GeyserBlocks};
+use solana_block_fetcher::core::geyser_proto::{self as proto, subscribe_update::UpdateOneof};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tonic::codec::{ProstCodec, Streaming};
+use tonic::codegen::{empty_body, http, BoxFuture, BoxStream, Context, Poll, Service};
+use tonic::server::{Grpc, NamedService, StreamingService};
+use tonic::transport::{Body, Server};
+
+const JITO_TIP_ACCOUNT: &str = "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5";
+const LEADER: &str = "1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM";
+
+fn proto_message(message: &Message) -> proto::Message {
+    proto::Message {
+        header: Some(proto::MessageHeader {
+            num_required_signatures: message.header.num_required_signatures as u32,
+            num_readonly_signed_accounts: message.header.num_readonly_signed_accounts as u32,
+            num_readonly_unsigned_accounts: message.header.num_readonly_unsigned_accounts as u32,
+        }),
+        account_keys: message.account_keys.iter().map(|k| k.to_bytes().to_vec()).collect(),
+        recent_blockhash: Hash::new_unique().to_bytes().to_vec(),
+        instructions: message
+            .instructions
+            .iter()
+            .map(|inst| proto::CompiledInstruction {
+                program_id_index: inst.program_id_index as u32,
+                accounts: inst.accounts.clone(),
+                data: inst.data.clone(),
+            })
+            .collect(),
+        versioned: false,
+        address_table_lookups: Vec::new(),
+    }
+}
+
+fn transaction_info(index: u64, message: proto::Message, fee: u64, failed: bool) -> proto::SubscribeUpdateTransactionInfo {
+    let signature = vec![index as u8 + 1; 64];
+    proto::SubscribeUpdateTransactionInfo {
+        signature: signature.clone(),
+        is_vote: false,
+        transaction: Some(proto::Transaction { signatures: vec![signature], message: Some(message) }),
+        meta: Some(proto::TransactionStatusMeta {
+            // bincode of TransactionError::AccountNotFound
+            err: failed.then(|| proto::TransactionError { err: vec![2, 0, 0, 0] }),
+            fee,
+            compute_units_consumed: Some(1_200),
+            ..Default::default()
+        }),
+        index,
+    }
+}
+
+fn streamed_block(slot: u64) -> proto::SubscribeUpdateBlock {
+    let payer = Pubkey::new_unique();
+    let tipped = Message::new(
+        &[
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(10_000),
+            system_instruction::transfer(&payer, &Pubkey::from_str(JITO_TIP_ACCOUNT).unwrap(), 10_000),
+        ],
+        Some(&payer),
+    );
+    let failed = Message::new(&[system_instruction::transfer(&payer, &Pubkey::new_unique(), 1)], Some(&payer));
+
+    proto::SubscribeUpdateBlock {
+        slot,
+        blockhash: Hash::new_unique().to_string(),
+        rewards: Some(proto::Rewards {
+            rewards: vec![proto::Reward {
+                pubkey: LEADER.to_string(),
+                lamports: 6_000,
+                post_balance: 1_000_000,
+                reward_type: proto::RewardType::Fee as i32,
+                commission: String::new(),
+            }],
+        }),
+        block_time: Some(proto::UnixTimestamp { timestamp: 1_708_000_000 }),
+        // Streamed out of block order
+        transactions: vec![
+            transaction_info(1, proto_message(&failed), 5_000, true),
+            transaction_info(0, proto_message(&tipped), 7_000, false),
+        ],
+        parent_slot: slot - 1,
+        parent_blockhash: Hash::new_unique().to_string(),
+    }
+}
+
+#[test]
+fn test_geyser_block_maps_to_transactions() {
+    let block = streamed_block(300);
+
+    let fetched = parse_geyser_block(&block).unwrap();
+
+    assert_eq!(fetched.row.slot, 300);
+    assert_eq!(fetched.row.parent_slot, 299);
+    assert_eq!(fetched.row.block_time, 1_708_000_000);
+    assert_eq!(fetched.row.transaction_count, 2);
+    assert_eq!(fetched.validator_key.as_deref(), Some(LEADER));
+
+    let tipped = &fetched.transactions[0];
+    assert_eq!(tipped.position, 1);
+    assert_eq!(tipped.landing_service, "Jito");
+    assert_eq!(tipped.tip_amount, 10_000);
+    assert_eq!(tipped.priority_fee, 2_000);
+    assert_eq!(tipped.compute_unit_limit, 200_000);
+    assert_eq!(tipped.compute_unit_price, 10_000);
+    assert_eq!(tipped.compute_units_consumed, 1_200);
+    assert_eq!(tipped.failed, 0);
+    assert_eq!(fetched.transactions[1].failed, 1);
+}
+
+#[test]
+fn test_geyser_v0_transaction_resolves_loaded_addresses() {
+    let payer = Pubkey::new_unique();
+    let jito_tip_account = Pubkey::from_str(JITO_TIP_ACCOUNT).unwrap();
+    // Index 2 is the tip account, loaded from a lookup table
+    let message = proto::Message {
+        header: Some(proto::MessageHeader { num_required_signatures: 1, num_readonly_signed_accounts: 0, num_readonly_unsigned_accounts: 1 }),
+        account_keys: vec![payer.to_bytes().to_vec(), solana_sdk::system_program::id().to_bytes().to_vec()],
+        recent_blockhash: Hash::new_unique().to_bytes().to_vec(),
+        instructions: vec![proto::CompiledInstruction {
+            program_id_index: 1,
+            accounts: vec![0, 2],
+            data: system_instruction::transfer(&payer, &jito_tip_account, 25_000).data,
+        }],
+        versioned: true,
+        address_table_lookups: vec![proto::MessageAddressTableLookup {
+            account_key: Pubkey::new_unique().to_bytes().to_vec(),
+            writable_indexes: vec![0],
+            readonly_indexes: Vec::new(),
+        }],
+    };
+    let mut info = transaction_info(0, message, 5_000, false);
+    info.meta.as_mut().unwrap().loaded_writable_addresses = vec![jito_tip_account.to_bytes().to_vec()];
+    let mut block = streamed_block(400);
+    block.transactions = vec![info];
+
+    let fetched = parse_geyser_block(&block).unwrap();
+
+    assert_eq!(fetched.transactions[0].landing_service, "Jito");
+    assert_eq!(fetched.transactions[0].tip_recipient, JITO_TIP_ACCOUNT);
+    assert_eq!(fetched.transactions[0].tip_amount, 25_000);
+}
+
+/// Answers a subscription with a ping and then the given blocks, reporting each request it receives
+#[derive(Clone)]
+struct StubGeyser {
+    blocks: Vec<proto::SubscribeUpdateBlock>,
+    requests: mpsc::UnboundedSender<(Option<String>, proto::SubscribeRequest)>,
+}
+
+impl StreamingService<proto::SubscribeRequest> for StubGeyser {
+    type Response = proto::SubscribeUpdate;
+    type ResponseStream = BoxStream<proto::SubscribeUpdate>;
+    type Future = BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+
+    fn call(&mut self, request: tonic::Request<Streaming<proto::SubscribeRequest>>) -> Self::Future {
+        let stub = self.clone();
+        Box::pin(async move {
+            let token = request.metadata().get("x-token").map(|v| v.to_str().unwrap().to_string());
+            let mut requests = request.into_inner();
+            let subscribe = requests.message().await?.unwrap();
+            stub.requests.send((token, subscribe)).unwrap();
+
+            let mut updates = vec![proto::SubscribeUpdate {
+                filters: Vec::new(),
+                update_oneof: Some(UpdateOneof::Ping(proto::SubscribeUpdatePing {})),
+            }];
+            updates.extend(stub.blocks.into_iter().map(|block| proto::SubscribeUpdate {
+                filters: vec!["blocks".to_string()],
+                update_oneof: Some(UpdateOneof::Block(block)),
+            }));
+            let stream: Self::ResponseStream = Box::pin(futures::stream::iter(updates.into_iter().map(Ok)));
+            Ok(tonic::Response::new(stream))
+        })
+    }
+}
+
+impl Service<http::Request<Body>> for StubGeyser {
+    type Response = http::Response<tonic::body::BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let stub = self.clone();
+        Box::pin(async move {
+            if request.uri().path() != proto::SUBSCRIBE_PATH {
+                return Ok(http::Response::builder().status(404).body(empty_body()).unwrap());
+            }
+            let mut grpc = Grpc::new(ProstCodec::<proto::SubscribeUpdate, proto::SubscribeRequest>::default());
+            Ok(grpc.streaming(stub, request).await)
+        })
+    }
+}
+
+impl NamedService for StubGeyser {
+    const NAME: &'static str = "geyser.Geyser";
+}
+
+async fn stub_server(blocks: Vec<proto::SubscribeUpdateBlock>) -> (String, mpsc::UnboundedReceiver<(Option<String>, proto::SubscribeRequest)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let (requests, received) = mpsc::unbounded_channel();
+    let incoming = futures::stream::unfold(listener, |listener| async move {
+        let connection = listener.accept().await.map(|(stream, _)| stream);
+        Some((connection, listener))
+    });
+    tokio::spawn(Server::builder().add_service(StubGeyser { blocks, requests }).serve_with_incoming(incoming));
+    (endpoint, received)
+}
+
+#[tokio::test]
+async fn test_geyser_subscription_streams_blocks() {
+    let (endpoint, mut requests) = stub_server(vec![streamed_block(500), streamed_block(501)]).await;
+
+    let mut blocks = GeyserBlocks::subscribe(&endpoint, Some("secret"), CommitmentConfig::confirmed()).await.unwrap();
+
+    // Pings are skipped
+    assert_eq!(blocks.next_block().await.unwrap().unwrap().slot, 500);
+    assert_eq!(blocks.next_block().await.unwrap().unwrap().slot, 501);
+    assert!(blocks.next_block().await.unwrap().is_none());
+
+    let (token, request) = requests.recv().await.unwrap();
+    assert_eq!(token.as_deref(), Some("secret"));
+    assert_eq!(request.commitment, Some(proto::CommitmentLevel::Confirmed as i32));
+    let filter = &request.blocks["blocks"];
+    assert_eq!(filter.include_transactions, Some(true));
+    assert_eq!(filter.include_accounts, Some(false));
+}
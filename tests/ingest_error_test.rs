@@ -0,0 +1,50 @@
+mod common;
+
+use solana_block_fetcher::core::block_fetcher::{is_at_tip, should_skip_old_slot};
+use solana_block_fetcher::core::ingest_error::IngestError;
+use common::rpc_response_error;
+
+#[test]
+fn test_client_errors_are_classified_without_string_matching_the_caller() {
+    let skipped = IngestError::from_client_error(7, rpc_response_error(-32007, "Slot 7 was skipped"));
+    let pruned = IngestError::from_client_error(7, rpc_response_error(-32001, "Block 7 cleaned up, does not exist on node. First available block: 100"));
+    let rate_limited = IngestError::from_client_error(7, rpc_response_error(429, "Too many requests for a specific RPC call"));
+    let unhealthy = IngestError::from_client_error(7, rpc_response_error(-32005, "Node is unhealthy"));
+
+    assert!(matches!(skipped, IngestError::SlotSkipped(7)));
+    assert!(matches!(pruned, IngestError::SlotPruned(7)));
+    assert!(matches!(rate_limited, IngestError::RateLimited(_)));
+    assert!(matches!(unhealthy, IngestError::Rpc(_)));
+}
+
+#[test]
+fn test_only_transient_errors_are_retryable() {
+    assert!(IngestError::Rpc("connection reset".into()).is_retryable());
+    assert!(IngestError::from_client_error(7, rpc_response_error(429, "Too many requests")).is_retryable());
+    assert!(IngestError::Sink("clickhouse unavailable".into()).is_retryable());
+    assert!(IngestError::NotStreamed(7).is_retryable());
+    assert!(!IngestError::SlotSkipped(7).is_retryable());
+    assert!(!IngestError::SlotPruned(7).is_retryable());
+    assert!(!IngestError::Decode("No transactions in block".to_string()).is_retryable());
+}
+
+#[test]
+fn test_should_skip_old_slot_only_for_pruned_slots_far_behind_the_tip() {
+    assert!(should_skip_old_slot(&IngestError::SlotPruned(1_000), 1_000, 2_000));
+    // Edge case: a pruned slot close to the tip is not jumped over
+    assert!(!should_skip_old_slot(&IngestError::SlotPruned(1_950), 1_950, 2_000));
+    assert!(!should_skip_old_slot(&IngestError::Rpc("connection reset".into()), 1_000, 2_000));
+}
+
+#[test]
+fn test_block_not_available_is_retryable_and_only_waited_out_at_the_tip() {
+    let not_available = IngestError::from_client_error(7, rpc_response_error(-32004, "Block not available for slot 7"));
+
+    assert!(matches!(not_available, IngestError::BlockNotAvailable(7)));
+    assert_eq!(not_available.kind(), "block_not_available");
+    assert!(not_available.is_retryable());
+
+    assert!(is_at_tip(2_000, 2_000));
+    assert!(is_at_tip(1_995, 2_000));
+    assert!(!is_at_tip(1_900, 2_000));
+}
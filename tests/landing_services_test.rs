@@ -0,0 +1,59 @@
+use solana_block_fetcher::analyzer::landing_services::{identify_landing_service, LandingServiceRegistry, BUNDLED_LANDING_SERVICES};
+
+#[test]
+fn test_bundled_registry_is_valid() {
+    // Every address must decode to a 32-byte pubkey and belong to a single service
+    let registry = LandingServiceRegistry::from_toml_str(BUNDLED_LANDING_SERVICES).unwrap();
+
+    let services: Vec<String> = registry.services().into_iter().map(|(name, _)| name).collect();
+    for expected in ["Jito", "bloXroute", "Nozomi", "NextBlock", "0slot", "Helius Sender", "Astralane"] {
+        assert!(services.contains(&expected.to_string()), "missing {}", expected);
+    }
+    assert_eq!(registry.identify("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"), Some("Jito"));
+}
+
+#[test]
+fn test_identify_uses_bundled_registry_by_default() {
+    assert_eq!(identify_landing_service("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"), Some("Jito".to_string()));
+    assert_eq!(identify_landing_service("11111111111111111111111111111111"), None);
+}
+
+#[test]
+fn test_registry_from_custom_file() {
+    let registry = LandingServiceRegistry::from_toml_str(
+        r#"
+        version = 1
+
+        [[services]]
+        name = "New Service"
+        accounts = ["9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(registry.version(), 1);
+    assert_eq!(registry.account_count(), 1);
+    assert_eq!(registry.identify("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"), Some("New Service"));
+}
+
+#[test]
+fn test_registry_rejects_invalid_files() {
+    // Edge case: not a base58 pubkey
+    let invalid_address = "version = 1\n[[services]]\nname = \"Bad\"\naccounts = [\"not-an-address\"]";
+    assert!(LandingServiceRegistry::from_toml_str(invalid_address).is_err());
+
+    // Edge case: the same account claimed by two services
+    let duplicate = r#"
+        version = 1
+        [[services]]
+        name = "A"
+        accounts = ["9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"]
+        [[services]]
+        name = "B"
+        accounts = ["9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"]
+    "#;
+    assert!(LandingServiceRegistry::from_toml_str(duplicate).is_err());
+
+    // Edge case: a format version this build does not understand
+    assert!(LandingServiceRegistry::from_toml_str("version = 2\nservices = []").is_err());
+}
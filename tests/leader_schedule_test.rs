@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use serde_json::json;
+use solana_block_fetcher::core::leader_schedule::{leader_from_rewards, EpochLeaders, LeaderScheduleCache};
+use solana_block_fetcher::core::rpc_pool::RpcPool;
+use solana_client::nonblocking::rpc_client::{Mocks, RpcClient};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::reward_type::RewardType;
+use solana_transaction_status::Reward;
+
+#[test]
+fn test_epoch_leaders_lookup_by_slot_index() {
+    let mut schedule = HashMap::new();
+    schedule.insert("validatorA".to_string(), vec![0, 1, 2, 3]);
+    schedule.insert("validatorB".to_string(), vec![4, 5, 6, 7]);
+
+    let leaders = EpochLeaders::from_schedule(&schedule, 8);
+
+    assert_eq!(leaders.leader(0), Some("validatorA"));
+    assert_eq!(leaders.leader(5), Some("validatorB"));
+    // Slot index past the end of the epoch
+    assert_eq!(leaders.leader(8), None);
+}
+
+#[test]
+fn test_epoch_leaders_unassigned_slots() {
+    // Edge case: schedule that does not cover every slot, plus an out-of-range index
+    let mut schedule = HashMap::new();
+    schedule.insert("validatorA".to_string(), vec![0, 100]);
+
+    let leaders = EpochLeaders::from_schedule(&schedule, 4);
+
+    assert_eq!(leaders.leader(0), Some("validatorA"));
+    assert_eq!(leaders.leader(1), None);
+}
+
+#[test]
+fn test_leader_from_rewards_uses_fee_reward() {
+    let rewards = vec![
+        Reward { pubkey: "voter".to_string(), lamports: 10, post_balance: 10, reward_type: Some(RewardType::Voting), commission: Some(5) },
+        Reward { pubkey: "leader".to_string(), lamports: 5000, post_balance: 5000, reward_type: Some(RewardType::Fee), commission: None },
+    ];
+
+    assert_eq!(leader_from_rewards(&rewards), Some("leader".to_string()));
+    assert_eq!(leader_from_rewards(&[]), None);
+}
+
+#[tokio::test]
+async fn test_leader_schedule_fetched_once_per_epoch() {
+    // Mocks are consumed on first use, so a second getLeaderSchedule call would return nothing
+    let mut mocks = Mocks::default();
+    mocks.insert(RpcRequest::GetLeaderSchedule, json!({ "validatorA": [0, 1, 2], "validatorB": [3] }));
+    let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+    let rpc_pool = RpcPool::from_clients(vec![("mock".to_string(), 0, client)], CommitmentConfig::finalized());
+    let cache = LeaderScheduleCache::new();
+
+    // The mock epoch schedule uses warmup epochs, so epoch 0 covers slots 0..32
+    assert_eq!(cache.get_leader(&rpc_pool, 3).await, Some("validatorB".to_string()));
+    assert_eq!(cache.get_leader(&rpc_pool, 1).await, Some("validatorA".to_string()));
+}
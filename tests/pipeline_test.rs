@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use futures::{FutureExt, StreamExt};
+use solana_block_fetcher::core::block_fetcher::BlockFetcher;
+use solana_block_fetcher::core::pipeline::fetch_blocks;
+use solana_block_fetcher::core::rpc_pool::RpcPool;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_transaction_status::UiTransactionEncoding;
+
+fn mock_fetcher() -> Arc<BlockFetcher> {
+    let client = RpcClient::new_mock("succeeds".to_string());
+    let rpc_pool = RpcPool::from_clients(vec![("succeeds".to_string(), 0, client)], CommitmentConfig::finalized());
+    Arc::new(BlockFetcher::new(Arc::new(rpc_pool), UiTransactionEncoding::Base64))
+}
+
+#[tokio::test]
+async fn test_fetch_blocks_preserves_slot_order() {
+    // The mock RPC answers every getBlock call, so results arrive out of order internally
+    let fetcher = mock_fetcher();
+    let slots = vec![7, 3, 5, 4, 6];
+
+    let results: Vec<_> = fetch_blocks(fetcher, slots.clone(), 3).collect().await;
+
+    let returned: Vec<u64> = results.iter().map(|(slot, _)| *slot).collect();
+    assert_eq!(returned, slots);
+    for (slot, fetched) in results {
+        let block = fetched.expect("mock block should be fetched");
+        assert_eq!(block.row.slot, slot);
+    }
+}
+
+#[tokio::test]
+async fn test_fetch_blocks_zero_concurrency() {
+    // Edge case: a concurrency of 0 is treated as 1 rather than stalling
+    let fetcher = mock_fetcher();
+
+    let results: Vec<_> = fetch_blocks(fetcher, vec![1, 2], 0).collect().await;
+
+    assert_eq!(results.len(), 2);
+}
+
+#[tokio::test]
+async fn test_dropping_fetch_blocks_aborts_fetches_in_flight() {
+    // The failing mock keeps each fetch in its retry backoff, holding a reference to the fetcher
+    let client = RpcClient::new_mock("fails".to_string());
+    let rpc_pool = RpcPool::from_clients(vec![("fails".to_string(), 0, client)], CommitmentConfig::finalized());
+    let fetcher = Arc::new(BlockFetcher::new(Arc::new(rpc_pool), UiTransactionEncoding::Base64));
+
+    let mut blocks = Box::pin(fetch_blocks(Arc::clone(&fetcher), vec![1, 2, 3], 3));
+    assert!(blocks.next().now_or_never().is_none());
+    drop(blocks);
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    assert_eq!(Arc::strong_count(&fetcher), 1);
+}
@@ -0,0 +1,45 @@
+use solana_block_fetcher::analyzer::program_fee::analyze_program_fees;
+use solana_block_fetcher::analyzer::types::Transaction;
+
+const JUPITER: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+
+#[test]
+fn test_program_fee_breakdown() {
+    let jupiter_tx = |fee: u64, priority_fee: u64, compute_unit_price: u64| Transaction {
+        slot: 1000,
+        fee,
+        base_fee: fee - priority_fee,
+        priority_fee,
+        compute_unit_price,
+        compute_unit_limit: if compute_unit_price > 0 { 300_000 } else { 0 },
+        program_ids: vec!["ComputeBudget111111111111111111111111111111".to_string(), JUPITER.to_string()],
+        ..Default::default()
+    };
+    let transactions = vec![jupiter_tx(5000, 0, 0), jupiter_tx(35000, 30000, 100_000), jupiter_tx(20000, 15000, 50_000)];
+
+    let rows = analyze_program_fees(&transactions, 1000, 1234567890);
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].program_name, "Jupiter");
+    assert_eq!(rows[0].total_fee, 60000);
+    assert_eq!(rows[0].total_base_fee, 15000);
+    assert_eq!(rows[0].total_priority_fee, 45000);
+    assert_eq!(rows[0].compute_unit_price_avg, 75_000.0);
+    assert_eq!(rows[0].compute_unit_limit_avg, 300_000.0);
+}
+
+#[test]
+fn test_programs_sharing_a_name_keep_separate_rows() {
+    // Edge case: older Jupiter versions are named by prefix, so program_id is what keeps their rows apart
+    let tx = |program_id: &str| Transaction { slot: 1000, fee: 5000, program_ids: vec![program_id.to_string()], ..Default::default() };
+    let transactions = vec![tx("JUP4Fb2cqiRUcaTHdrPC8h2gNsA5ETXEPDD5ZBwq2Ps"), tx("JUP3c2Uh3WA4Ng34tw6kPd2G4C5BB21Xo36Je1s32Ph")];
+
+    let mut rows = analyze_program_fees(&transactions, 1000, 1234567890);
+    rows.sort_by(|a, b| a.program_id.cmp(&b.program_id));
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].program_name, rows[1].program_name);
+    assert_eq!(rows[0].program_type, rows[1].program_type);
+    assert_eq!(rows[0].program_id, "JUP3c2Uh3WA4Ng34tw6kPd2G4C5BB21Xo36Je1s32Ph");
+    assert_eq!(rows[1].program_id, "JUP4Fb2cqiRUcaTHdrPC8h2gNsA5ETXEPDD5ZBwq2Ps");
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use futures::future::BoxFuture;
+use solana_block_fetcher::analyzer::registry::{Analyzer, AnalyzerRegistry, BlockContext, RowBatch};
+use solana_block_fetcher::core::block_fetcher::{parse_block, store_block};
+use solana_block_fetcher::core::config::RetryConfig;
+use solana_block_fetcher::core::leader_schedule::leader_from_rewards;
+use solana_block_fetcher::core::retry::RetryPolicy;
+use solana_block_fetcher::core::replay::{parse_recorded_block, run_replay, BlockRecorder, Recording};
+use solana_block_fetcher::core::sink::{JsonlSink, Sink, SinkError};
+use common::output_dir;
+
+const FIXTURES: &str = "tests/fixtures/blocks";
+const FIXTURE_SLOT: u64 = 250_000_000;
+
+fn read_jsonl(path: &Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(path).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect()
+}
+
+#[test]
+fn test_recorded_block_parses_into_transactions() {
+    let recording = Recording::open(Path::new(FIXTURES)).unwrap();
+    assert_eq!(recording.slots(), vec![FIXTURE_SLOT]);
+
+    let block = recording.load(0).unwrap();
+    let validator_key = block.rewards.as_deref().and_then(leader_from_rewards);
+    let fetched = parse_block(FIXTURE_SLOT, &block, validator_key).unwrap();
+
+    assert_eq!(fetched.row.transaction_count, 3);
+    assert_eq!(fetched.validator_key.as_deref(), Some("1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM"));
+    let tipped = &fetched.transactions[0];
+    assert_eq!(tipped.landing_service, "Jito");
+    assert_eq!(tipped.tip_amount, 10_000);
+    assert_eq!(tipped.priority_fee, 2_000);
+    assert_eq!(tipped.compute_unit_price, 10_000);
+    assert_eq!(tipped.compute_unit_limit, 200_000);
+    assert_eq!(fetched.transactions[2].failed, 1);
+}
+
+#[tokio::test]
+async fn test_replay_produces_expected_analysis_rows() {
+    // Regression fixture: the same recorded block must always produce the same rows
+    let dir = output_dir("replay");
+    let sink = JsonlSink::new(&dir).unwrap();
+
+    run_replay(&sink, Path::new(FIXTURES), &AnalyzerRegistry::builtin(), &RetryPolicy::default()).await.unwrap();
+
+    let blocks = read_jsonl(&dir.join("blocks.jsonl"));
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0]["slot"], FIXTURE_SLOT);
+    assert_eq!(blocks[0]["transaction_count"], 3);
+
+    let fees = read_jsonl(&dir.join("fee_landscape.jsonl"));
+    assert_eq!(fees[0]["total_base_fee"], 15_000);
+    assert_eq!(fees[0]["total_priority_fee"], 2_000);
+    assert_eq!(fees[0]["total_tip"], 10_000);
+    assert_eq!(fees[0]["total_compute_units"], 1_500);
+
+    let bundling = read_jsonl(&dir.join("bundling_analysis.jsonl"));
+    assert_eq!(bundling[0]["landing_service"], "Jito");
+    assert_eq!(bundling[0]["validator_key"], "1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_recorded_archive_is_replayed_in_slot_order() {
+    let dir = output_dir("archive");
+    let archive = dir.join("blocks.jsonl");
+    let block = Recording::open(Path::new(FIXTURES)).unwrap().load(0).unwrap();
+
+    // Concurrent fetches can record blocks out of order
+    let recorder = BlockRecorder::open(&archive).unwrap();
+    recorder.record(12, &block).unwrap();
+    recorder.record(11, &block).unwrap();
+    drop(recorder);
+
+    let recording = Recording::open(&archive).unwrap();
+    assert_eq!(recording.slots(), vec![11, 12]);
+    assert_eq!(recording.load(1).unwrap().blockhash, block.blockhash);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_parse_recorded_block_accepts_rpc_response() {
+    let block = std::fs::read_to_string(Path::new(FIXTURES).join(format!("{}.json", FIXTURE_SLOT))).unwrap();
+    let response = format!(r#"{{"jsonrpc":"2.0","result":{},"id":1}}"#, block);
+
+    let parsed = parse_recorded_block(&response).unwrap();
+
+    assert_eq!(parsed.parent_slot, FIXTURE_SLOT - 1);
+    assert!(parse_recorded_block("{\"result\": null}").is_err());
+}
+
+/// Counts how often it is run, like a stateful analyzer would notice
+struct CountingAnalyzer {
+    calls: Arc<AtomicUsize>,
+}
+
+impl Analyzer for CountingAnalyzer {
+    fn name(&self) -> &'static str {
+        "counting"
+    }
+
+    fn table(&self) -> &'static str {
+        "counted"
+    }
+
+    fn schema(&self) -> &'static str {
+        ""
+    }
+
+    fn analyze(&self, block: &BlockContext) -> Box<dyn RowBatch> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Box::new(vec![block.slot])
+    }
+}
+
+/// Fails the first write to the `counted` table and keeps the table of every write that succeeded
+#[derive(Default)]
+struct FlakySink {
+    failed: Mutex<bool>,
+    written: Mutex<Vec<String>>,
+}
+
+impl Sink for FlakySink {
+    fn write<'a>(&'a self, table: &'a str, _rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            let mut failed = self.failed.lock().unwrap();
+            if table == "counted" && !*failed {
+                *failed = true;
+                return Err("connection reset".into());
+            }
+            self.written.lock().unwrap().push(table.to_string());
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_store_block_retries_only_the_failed_write() {
+    let recording = Recording::open(Path::new(FIXTURES)).unwrap();
+    let fetched = parse_block(FIXTURE_SLOT, &recording.load(0).unwrap(), None).unwrap();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let analyzers = AnalyzerRegistry::new(vec![Box::new(CountingAnalyzer { calls: Arc::clone(&calls) })]);
+    let policy = RetryPolicy::new(&RetryConfig { max_attempts: 3, base_delay_ms: 1, max_delay_ms: 1, rate_limit_delay_ms: 1 });
+    let sink = FlakySink::default();
+
+    store_block(&sink, &fetched, &analyzers, &policy).await.unwrap();
+
+    // The block was analyzed once, and the blocks row that was stored is not written again
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(*sink.written.lock().unwrap(), vec!["blocks", "counted"]);
+}
@@ -0,0 +1,127 @@
+use std::cell::Cell;
+use std::time::Duration;
+use solana_block_fetcher::core::config::RetryConfig;
+use solana_block_fetcher::core::ingest_error::IngestError;
+use solana_block_fetcher::core::retry::RetryPolicy;
+use solana_client::client_error::ClientErrorKind;
+use solana_client::rpc_request::{RpcError, RpcResponseErrorData};
+
+fn policy(max_attempts: u32, base_delay_ms: u64, max_delay_ms: u64) -> RetryPolicy {
+    RetryPolicy::new(&RetryConfig { max_attempts, base_delay_ms, max_delay_ms, rate_limit_delay_ms: 2_000 })
+}
+
+fn rate_limited() -> IngestError {
+    let err = ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code: 429,
+        message: "Too many requests".to_string(),
+        data: RpcResponseErrorData::Empty,
+    });
+    IngestError::from_client_error(1, err.into())
+}
+
+#[test]
+fn test_backoff_doubles_up_to_the_maximum() {
+    let policy = policy(6, 500, 3_000);
+    let err = IngestError::Rpc("connection reset".into());
+
+    assert_eq!(policy.backoff(&err, 1), Some(Duration::from_millis(500)));
+    assert_eq!(policy.backoff(&err, 2), Some(Duration::from_millis(1_000)));
+    assert_eq!(policy.backoff(&err, 3), Some(Duration::from_millis(2_000)));
+    assert_eq!(policy.backoff(&err, 4), Some(Duration::from_millis(3_000)));
+    // Out of attempts
+    assert_eq!(policy.backoff(&err, 6), None);
+}
+
+#[test]
+fn test_backoff_rules_per_error_kind() {
+    let policy = policy(3, 500, 30_000);
+
+    assert_eq!(policy.backoff(&rate_limited(), 1), Some(Duration::from_millis(2_000)));
+    assert_eq!(policy.backoff(&IngestError::Sink("clickhouse unavailable".into()), 1), Some(Duration::from_millis(500)));
+    assert_eq!(policy.backoff(&IngestError::SlotSkipped(1), 1), None);
+    assert_eq!(policy.backoff(&IngestError::SlotPruned(1), 1), None);
+    assert_eq!(policy.backoff(&IngestError::Decode("No transactions in block".to_string()), 1), None);
+    assert_eq!(policy.attempts_for(&rate_limited()), 3);
+    assert_eq!(policy.attempts_for(&IngestError::SlotPruned(1)), 1);
+}
+
+#[test]
+fn test_jittered_delay_stays_within_the_upper_half_of_the_backoff() {
+    let policy = policy(3, 1_000, 30_000);
+    let err = IngestError::Rpc("connection reset".into());
+
+    for _ in 0..100 {
+        let delay = policy.next_delay(&err, 2).unwrap();
+        assert!(delay >= Duration::from_millis(1_000) && delay <= Duration::from_millis(2_000), "{:?}", delay);
+    }
+}
+
+#[tokio::test]
+async fn test_retry_stops_at_max_attempts_or_on_permanent_errors() {
+    let policy = policy(3, 1, 1);
+
+    let calls = Cell::new(0);
+    let result: Result<(), IngestError> = policy
+        .retry(1, || {
+            calls.set(calls.get() + 1);
+            async { Err(IngestError::Rpc("connection reset".into())) }
+        })
+        .await;
+    assert!(matches!(result, Err(IngestError::Rpc(_))));
+    assert_eq!(calls.get(), 3);
+
+    calls.set(0);
+    let result: Result<(), IngestError> = policy
+        .retry(1, || {
+            calls.set(calls.get() + 1);
+            async { Err(IngestError::SlotSkipped(1)) }
+        })
+        .await;
+    assert!(matches!(result, Err(IngestError::SlotSkipped(1))));
+    assert_eq!(calls.get(), 1);
+
+    // Succeeds on the second attempt
+    calls.set(0);
+    let result = policy
+        .retry(1, || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move { if attempt < 2 { Err(IngestError::Sink("timeout".into())) } else { Ok(attempt) } }
+        })
+        .await;
+    assert_eq!(result.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_retry_if_returns_unwanted_retries_right_away() {
+    let policy = policy(3, 1, 1);
+
+    let calls = Cell::new(0);
+    let result: Result<(), IngestError> = policy
+        .retry_if(
+            1,
+            |e| !matches!(e, IngestError::BlockNotAvailable(_)),
+            || {
+                calls.set(calls.get() + 1);
+                async { Err(IngestError::BlockNotAvailable(1)) }
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(IngestError::BlockNotAvailable(1))));
+    assert_eq!(calls.get(), 1);
+
+    // Other retryable errors are still retried
+    calls.set(0);
+    let result: Result<(), IngestError> = policy
+        .retry_if(
+            1,
+            |e| !matches!(e, IngestError::BlockNotAvailable(_)),
+            || {
+                calls.set(calls.get() + 1);
+                async { Err(IngestError::Rpc("connection reset".into())) }
+            },
+        )
+        .await;
+    assert!(matches!(result, Err(IngestError::Rpc(_))));
+    assert_eq!(calls.get(), 3);
+}
@@ -0,0 +1,156 @@
+mod common;
+
+use std::time::{Duration, Instant};
+use solana_block_fetcher::core::rpc_pool::{
+    is_block_not_available, is_rate_limited, is_slot_pruned, is_slot_skipped, redact_url, EndpointHealth, RpcPool, CIRCUIT_BREAKER_THRESHOLD,
+};
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_request::RpcError;
+use solana_sdk::commitment_config::CommitmentConfig;
+use common::rpc_response_error;
+
+#[test]
+fn test_health_rate_limit_backoff_grows_and_resets() {
+    let now = Instant::now();
+    let mut health = EndpointHealth::default();
+
+    health.record_rate_limit(now);
+    assert_eq!(health.backoff_until, Some(now + Duration::from_secs(1)));
+    health.record_rate_limit(now);
+    assert_eq!(health.backoff_until, Some(now + Duration::from_secs(2)));
+    assert!(health.is_backing_off(now));
+
+    // A successful request clears the backoff
+    health.record_success(Duration::from_millis(50));
+    assert!(!health.is_backing_off(now));
+    assert_eq!(health.rate_limits, 0);
+}
+
+#[test]
+fn test_health_rate_limit_backoff_is_capped() {
+    let now = Instant::now();
+    let mut health = EndpointHealth::default();
+    for _ in 0..20 {
+        health.record_rate_limit(now);
+    }
+
+    assert_eq!(health.backoff_until, Some(now + Duration::from_secs(60)));
+}
+
+#[test]
+fn test_health_score_penalizes_errors() {
+    let mut fast_but_failing = EndpointHealth::default();
+    fast_but_failing.record_success(Duration::from_millis(50));
+    for _ in 0..5 {
+        fast_but_failing.record_failure(Instant::now());
+    }
+
+    let mut slow_but_healthy = EndpointHealth::default();
+    slow_but_healthy.record_success(Duration::from_millis(200));
+
+    assert!(slow_but_healthy.score() < fast_but_failing.score());
+}
+
+#[test]
+fn test_error_classification() {
+    assert!(is_rate_limited(&rpc_response_error(429, "Too many requests for a specific RPC call")));
+    assert!(is_rate_limited(&rpc_response_error(-32000, "Too Many Requests")));
+    assert!(!is_rate_limited(&rpc_response_error(-32004, "Block not available for slot 100")));
+
+    assert!(is_slot_skipped(&rpc_response_error(-32007, "Slot 100 was skipped, or missing due to ledger jump to recent snapshot")));
+    assert!(is_slot_skipped(&rpc_response_error(-32009, "Slot 100 was skipped, or missing in long-term storage")));
+    assert!(!is_slot_skipped(&rpc_response_error(-32001, "Block 100 cleaned up")));
+
+    assert!(is_block_not_available(&rpc_response_error(-32004, "Block not available for slot 100")));
+    assert!(!is_block_not_available(&rpc_response_error(-32000, "Block not available for slot 100")));
+}
+
+#[test]
+fn test_error_codes_take_precedence_over_messages() {
+    // A specific code decides, whatever the message says
+    assert!(!is_slot_pruned(&rpc_response_error(-32004, "Block 100 does not exist yet")));
+    assert!(!is_rate_limited(&rpc_response_error(-32005, "Node is behind, too many requests queued")));
+    assert!(is_slot_pruned(&rpc_response_error(-32001, "Block 100 cleaned up")));
+
+    // Fallback: providers that send no code, or only the generic server error code
+    assert!(is_slot_pruned(&rpc_response_error(-32000, "Block 100 cleaned up, does not exist on node")));
+    let uncoded: ClientError = ClientErrorKind::RpcError(RpcError::ForUser("Too many requests".to_string())).into();
+    assert!(is_rate_limited(&uncoded));
+    assert!(!is_slot_skipped(&rpc_response_error(-32000, "Slot 100 was skipped")));
+}
+
+#[test]
+fn test_redact_url_hides_api_key_path() {
+    assert_eq!(redact_url("https://go.getblock.us/1234abcd"), "https://go.getblock.us/...");
+    assert_eq!(redact_url("https://api.mainnet-beta.solana.com"), "https://api.mainnet-beta.solana.com");
+}
+
+#[tokio::test]
+async fn test_pool_fails_over_to_next_endpoint() {
+    // The "fails" mock answers every request with null, which cannot be decoded
+    let pool = RpcPool::from_clients(
+        vec![
+            ("primary".to_string(), 0, RpcClient::new_mock("fails".to_string())),
+            ("fallback".to_string(), 1, RpcClient::new_mock("succeeds".to_string())),
+        ],
+        CommitmentConfig::finalized(),
+    );
+
+    assert_eq!(pool.get_slot().await.unwrap(), 0);
+
+    let health = pool.health();
+    assert_eq!(health[0].1.failures, 1);
+    assert_eq!(health[1].1.failures, 0);
+    assert_eq!(health[1].1.requests, 1);
+}
+
+#[tokio::test]
+async fn test_pool_all_endpoints_failing() {
+    let pool = RpcPool::from_clients(
+        vec![("primary".to_string(), 0, RpcClient::new_mock("fails".to_string()))],
+        CommitmentConfig::finalized(),
+    );
+
+    assert!(pool.get_slot().await.is_err());
+}
+
+#[test]
+fn test_health_consecutive_failures_open_the_circuit() {
+    let now = Instant::now();
+    let mut health = EndpointHealth::default();
+    for _ in 1..CIRCUIT_BREAKER_THRESHOLD {
+        health.record_failure(now);
+    }
+    assert!(!health.is_backing_off(now));
+
+    health.record_failure(now);
+    assert_eq!(health.backoff_until, Some(now + Duration::from_secs(5)));
+    health.record_failure(now);
+    assert_eq!(health.backoff_until, Some(now + Duration::from_secs(10)));
+
+    // A successful request closes it again
+    health.record_success(Duration::from_millis(50));
+    assert!(!health.is_backing_off(now));
+    assert_eq!(health.consecutive_failures, 0);
+}
+
+#[tokio::test]
+async fn test_pool_skips_primary_after_repeated_failures() {
+    let pool = RpcPool::from_clients(
+        vec![
+            ("primary".to_string(), 0, RpcClient::new_mock("fails".to_string())),
+            ("fallback".to_string(), 1, RpcClient::new_mock("succeeds".to_string())),
+        ],
+        CommitmentConfig::finalized(),
+    );
+
+    for _ in 0..CIRCUIT_BREAKER_THRESHOLD + 2 {
+        assert_eq!(pool.get_slot().await.unwrap(), 0);
+    }
+
+    // Once its circuit opened, the failing primary is no longer tried first
+    let health = pool.health();
+    assert_eq!(health[0].1.failures, CIRCUIT_BREAKER_THRESHOLD as u64);
+    assert_eq!(health[1].1.requests, CIRCUIT_BREAKER_THRESHOLD as u64 + 2);
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use std::fs::File;
+use std::path::PathBuf;
+use arrow_schema::DataType;
+use clickhouse::Client;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use solana_block_fetcher::analyzer::bundling::BundlingAnalysisRow;
+use solana_block_fetcher::analyzer::fee_by_type::FeeByTransactionTypeRow;
+use solana_block_fetcher::analyzer::columns::{columns_of, ColumnType};
+use solana_block_fetcher::analyzer::registry::RowBatch;
+use solana_block_fetcher::analyzer::tip_candidates::TipAccountCandidateRow;
+use solana_block_fetcher::core::sink::{arrow_schema, ClickHouseSink, JsonlSink, ParquetSink, Sink};
+use solana_block_fetcher::core::slot_status::SlotStatusRow;
+use common::output_dir;
+
+fn fee_rows(slot: u64) -> Vec<FeeByTransactionTypeRow> {
+    vec![
+        FeeByTransactionTypeRow { slot, block_time: 1234567890, transaction_type: "jupiter".to_string(), transaction_count: 3, total_fee: 15000 },
+        FeeByTransactionTypeRow { slot, block_time: 1234567890, transaction_type: "vote".to_string(), transaction_count: 2, total_fee: 10000 },
+    ]
+}
+
+#[tokio::test]
+async fn test_jsonl_sink_appends_rows_per_table() {
+    let dir = output_dir("jsonl");
+    let sink = JsonlSink::new(&dir).unwrap();
+
+    sink.write("fee_by_transaction_type", &fee_rows(100)).await.unwrap();
+    sink.write("fee_by_transaction_type", &fee_rows(101)).await.unwrap();
+    // Empty batches do not create files
+    sink.write("program_fee_analysis", &Vec::<FeeByTransactionTypeRow>::new()).await.unwrap();
+
+    let contents = std::fs::read_to_string(dir.join("fee_by_transaction_type.jsonl")).unwrap();
+    let lines: Vec<serde_json::Value> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0]["slot"], 100);
+    assert_eq!(lines[0]["transaction_type"], "jupiter");
+    assert_eq!(lines[3]["slot"], 101);
+    assert_eq!(lines[3]["total_fee"], 10000);
+    assert!(!dir.join("program_fee_analysis.jsonl").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_parquet_sink_writes_buffered_rows_on_flush() {
+    let dir = output_dir("parquet");
+    let sink = ParquetSink::new(&dir).unwrap();
+
+    sink.write("fee_by_transaction_type", &fee_rows(100)).await.unwrap();
+    sink.write("fee_by_transaction_type", &fee_rows(101)).await.unwrap();
+    // Rows are buffered until enough accumulate or the sink is flushed
+    assert!(!dir.join("fee_by_transaction_type").exists());
+
+    sink.flush().await.unwrap();
+
+    let files: Vec<PathBuf> = std::fs::read_dir(dir.join("fee_by_transaction_type"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&files[0]).unwrap()).unwrap().build().unwrap();
+    let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+    let schema = batches[0].schema();
+    let columns: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+    assert_eq!(columns, vec!["slot", "block_time", "transaction_type", "transaction_count", "total_fee"]);
+
+    // Flushing again with nothing buffered writes no file
+    sink.flush().await.unwrap();
+    assert_eq!(std::fs::read_dir(dir.join("fee_by_transaction_type")).unwrap().count(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_arrow_schema_follows_row_type() {
+    // No rows are needed, the schema comes from the row type
+    let columns = Vec::<BundlingAnalysisRow>::new().columns().unwrap();
+
+    let schema = arrow_schema(&columns);
+
+    assert_eq!(schema.field_with_name("slot").unwrap().data_type(), &DataType::UInt64);
+    assert_eq!(schema.field_with_name("largest_bundle_size").unwrap().data_type(), &DataType::UInt32);
+    assert_eq!(schema.field_with_name("landing_service").unwrap().data_type(), &DataType::Utf8);
+    let validator_key = schema.field_with_name("validator_key").unwrap();
+    assert_eq!(validator_key.data_type(), &DataType::Utf8);
+    assert!(validator_key.is_nullable());
+    assert!(!schema.field_with_name("slot").unwrap().is_nullable());
+}
+
+#[test]
+fn test_columns_of_nullable_numbers_and_lists() {
+    let slot_status = columns_of::<SlotStatusRow>().unwrap();
+    let epoch = slot_status.iter().find(|c| c.name == "epoch").unwrap();
+    // Edge case: an optional number stays a number, even when every row has it unset
+    assert_eq!(epoch.column_type, ColumnType::UInt64);
+    assert!(epoch.nullable);
+
+    let candidates = columns_of::<TipAccountCandidateRow>().unwrap();
+    let names: Vec<&str> = candidates.iter().map(|c| c.name).collect();
+    assert_eq!(names.first(), Some(&"recipient"));
+    assert_eq!(names.last(), Some(&"validators"));
+    assert_eq!(candidates.last().unwrap().column_type, ColumnType::List(Box::new(ColumnType::String)));
+}
+
+#[tokio::test]
+async fn test_parquet_files_of_a_table_share_its_schema() {
+    let dir = output_dir("parquet_schema");
+    let sink = ParquetSink::new(&dir).unwrap();
+    let row = |slot: u64, validator_key: Option<&str>| BundlingAnalysisRow {
+        slot,
+        blockhash: "hash".to_string(),
+        block_time: 1234567890,
+        largest_bundle_size: 2,
+        validator_key: validator_key.map(str::to_string),
+        landing_service: "Jito".to_string(),
+        landing_service_count: 4,
+        cpi_tip_count: 0,
+    };
+
+    // Only the first file has a leader for every row
+    sink.write("bundling_analysis", &vec![row(100, Some("validator"))]).await.unwrap();
+    sink.flush().await.unwrap();
+    sink.write("bundling_analysis", &vec![row(101, None)]).await.unwrap();
+    sink.flush().await.unwrap();
+
+    let schemas: Vec<_> = std::fs::read_dir(dir.join("bundling_analysis"))
+        .unwrap()
+        .map(|entry| {
+            let file = File::open(entry.unwrap().path()).unwrap();
+            ParquetRecordBatchReaderBuilder::try_new(file).unwrap().schema().clone()
+        })
+        .collect();
+    assert_eq!(schemas.len(), 2);
+    assert_eq!(schemas[0], schemas[1]);
+
+    // Rows of another type are rejected rather than written with a different schema
+    assert!(sink.write("bundling_analysis", &fee_rows(102)).await.is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_parquet_flush_due_keeps_recent_rows_buffered() {
+    let dir = output_dir("parquet_due");
+    let sink = ParquetSink::new(&dir).unwrap();
+
+    sink.write("fee_by_transaction_type", &fee_rows(100)).await.unwrap();
+    sink.flush_due().await.unwrap();
+
+    // Neither enough rows nor old enough to be written yet
+    assert!(!dir.join("fee_by_transaction_type").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_parquet_flush_keeps_rows_when_the_file_fails() {
+    let dir = output_dir("parquet_failed");
+    let sink = ParquetSink::new(&dir).unwrap();
+    // A file where the table directory belongs makes the write fail
+    std::fs::write(dir.join("fee_by_transaction_type"), b"").unwrap();
+
+    sink.write("fee_by_transaction_type", &fee_rows(100)).await.unwrap();
+    assert!(sink.flush().await.is_err());
+    sink.write("fee_by_transaction_type", &fee_rows(101)).await.unwrap();
+
+    std::fs::remove_file(dir.join("fee_by_transaction_type")).unwrap();
+    sink.flush().await.unwrap();
+
+    let files: Vec<PathBuf> = std::fs::read_dir(dir.join("fee_by_transaction_type"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&files[0]).unwrap()).unwrap().build().unwrap();
+    let batches: Vec<_> = reader.map(|batch| batch.unwrap()).collect();
+    // The rows of the failed flush are written along with those buffered after it
+    assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 4);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_clickhouse_sink_rejects_rows_of_another_type() {
+    // Neither write reaches the server, so no ClickHouse is needed
+    let sink = ClickHouseSink::new(Client::default());
+
+    let err = sink.write("blocks", &fee_rows(100)).await.unwrap_err();
+    assert!(err.to_string().contains("BlockRow"), "{}", err);
+    assert!(sink.write("no_such_table", &fee_rows(100)).await.is_err());
+    // Empty batches of the right type are skipped without a request
+    sink.write("fee_by_transaction_type", &Vec::<FeeByTransactionTypeRow>::new()).await.unwrap();
+}
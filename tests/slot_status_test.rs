@@ -0,0 +1,68 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use futures::future::BoxFuture;
+use serde_json::json;
+use solana_block_fetcher::analyzer::registry::RowBatch;
+use solana_block_fetcher::core::block_fetcher::BlockFetcher;
+use solana_block_fetcher::core::ingest_error::IngestError;
+use solana_block_fetcher::core::rpc_pool::RpcPool;
+use solana_block_fetcher::core::sink::{Sink, SinkError};
+use solana_block_fetcher::core::slot_status::{classify_fetch_error, record_slot_status, SlotStatus};
+use solana_client::nonblocking::rpc_client::{Mocks, RpcClient};
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_transaction_status::UiTransactionEncoding;
+use common::rpc_response_error;
+
+/// Keeps every row written, as JSON
+#[derive(Default)]
+struct MemorySink {
+    rows: Mutex<Vec<(String, serde_json::Value)>>,
+}
+
+impl Sink for MemorySink {
+    fn write<'a>(&'a self, table: &'a str, rows: &'a dyn RowBatch) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            let rows = rows.to_json()?;
+            self.rows.lock().unwrap().extend(rows.into_iter().map(|row| (table.to_string(), row)));
+            Ok(())
+        })
+    }
+}
+
+#[test]
+fn test_classify_fetch_error_only_trusts_skipped_slot_codes() {
+    let skipped = rpc_response_error(-32007, "Slot 5 was skipped, or missing due to ledger jump to recent snapshot");
+    let long_term_skipped = rpc_response_error(-32009, "Slot 5 was skipped, or missing in long-term storage");
+    let not_available = rpc_response_error(-32004, "Block not available for slot 5");
+
+    assert_eq!(classify_fetch_error(&IngestError::from_client_error(5, skipped)), SlotStatus::Skipped);
+    assert_eq!(classify_fetch_error(&IngestError::from_client_error(5, long_term_skipped)), SlotStatus::Skipped);
+    // Edge case: a block that is not available yet may still be produced
+    assert_eq!(classify_fetch_error(&IngestError::from_client_error(5, not_available)), SlotStatus::Unavailable);
+    assert_eq!(classify_fetch_error(&IngestError::Sink("connection reset".into())), SlotStatus::Unavailable);
+}
+
+#[tokio::test]
+async fn test_record_slot_status_includes_epoch_and_scheduled_leader() {
+    let mut mocks = Mocks::default();
+    mocks.insert(RpcRequest::GetLeaderSchedule, json!({ "validatorA": [0, 1, 2], "validatorB": [3] }));
+    let client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+    let rpc_pool = RpcPool::from_clients(vec![("mock".to_string(), 0, client)], CommitmentConfig::finalized());
+    let fetcher = Arc::new(BlockFetcher::new(Arc::new(rpc_pool), UiTransactionEncoding::Base64));
+    let sink = MemorySink::default();
+
+    record_slot_status(&sink, &fetcher, 3, SlotStatus::Skipped, "skipped".to_string()).await;
+
+    let rows = sink.rows.lock().unwrap();
+    assert_eq!(rows.len(), 1);
+    let (table, row) = &rows[0];
+    assert_eq!(table, "slot_status");
+    assert_eq!(row["slot"], 3);
+    // The mock epoch schedule uses warmup epochs, so slot 3 is in epoch 0
+    assert_eq!(row["epoch"], 0);
+    assert_eq!(row["status"], "skipped");
+    assert_eq!(row["leader"], "validatorB");
+    assert_eq!(row["error"], "skipped");
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use solana_block_fetcher::core::config::SubscriptionKind;
+use solana_block_fetcher::core::slot_subscription::SlotSubscription;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+const SUBSCRIPTION_ID: u64 = 42;
+const WAIT: Duration = Duration::from_secs(5);
+
+/// Accept one PubSub connection and answer its subscribe request, returning the method it asked for
+async fn accept_subscriber(listener: &TcpListener) -> (WebSocketStream<TcpStream>, String) {
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+    loop {
+        let request: Value = match ws.next().await.unwrap().unwrap() {
+            Message::Text(text) => serde_json::from_str(&text).unwrap(),
+            _ => continue,
+        };
+        let response = json!({ "jsonrpc": "2.0", "result": SUBSCRIPTION_ID, "id": request["id"] });
+        ws.send(Message::Text(response.to_string())).await.unwrap();
+        return (ws, request["method"].as_str().unwrap().to_string());
+    }
+}
+
+async fn notify(ws: &mut WebSocketStream<TcpStream>, method: &str, result: Value) {
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": { "result": result, "subscription": SUBSCRIPTION_ID }
+    });
+    ws.send(Message::Text(notification.to_string())).await.unwrap();
+}
+
+async fn mock_server() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+#[tokio::test]
+async fn test_slot_subscription_reports_root_when_finalized() {
+    let (listener, url) = mock_server().await;
+    let mut subscription = SlotSubscription::spawn(&url, SubscriptionKind::Slot, CommitmentConfig::finalized());
+
+    let (mut ws, method) = accept_subscriber(&listener).await;
+    assert_eq!(method, "slotSubscribe");
+    notify(&mut ws, "slotNotification", json!({ "parent": 99, "root": 68, "slot": 100 })).await;
+
+    assert_eq!(subscription.wait_for_slot(WAIT).await, Some(68));
+    assert!(subscription.is_connected());
+    assert!(subscription.is_authoritative());
+
+    // Older roots never move the tip backwards
+    notify(&mut ws, "slotNotification", json!({ "parent": 100, "root": 60, "slot": 101 })).await;
+    notify(&mut ws, "slotNotification", json!({ "parent": 101, "root": 69, "slot": 102 })).await;
+    assert_eq!(subscription.wait_for_slot(WAIT).await, Some(69));
+}
+
+#[tokio::test]
+async fn test_block_subscription_reports_confirmed_blocks() {
+    let (listener, url) = mock_server().await;
+    let mut subscription = SlotSubscription::spawn(&url, SubscriptionKind::Block, CommitmentConfig::confirmed());
+
+    let (mut ws, method) = accept_subscriber(&listener).await;
+    assert_eq!(method, "blockSubscribe");
+    // A block the node could not load is not announced
+    notify(&mut ws, "blockNotification", json!({
+        "context": { "slot": 200 },
+        "value": { "slot": 200, "block": null, "err": "BlockStoreError" }
+    }))
+    .await;
+    notify(&mut ws, "blockNotification", json!({
+        "context": { "slot": 201 },
+        "value": { "slot": 201, "block": null, "err": null }
+    }))
+    .await;
+
+    assert_eq!(subscription.wait_for_slot(WAIT).await, Some(201));
+    assert!(subscription.is_authoritative());
+}
+
+#[tokio::test]
+async fn test_slot_subscription_reconnects_after_socket_drops() {
+    let (listener, url) = mock_server().await;
+    let mut subscription = SlotSubscription::spawn(&url, SubscriptionKind::Slot, CommitmentConfig::confirmed());
+    // Confirmed slots cannot be read from slotSubscribe, so the tip still comes from the RPC
+    assert!(!subscription.is_authoritative());
+
+    let (mut ws, _) = accept_subscriber(&listener).await;
+    notify(&mut ws, "slotNotification", json!({ "parent": 299, "root": 268, "slot": 300 })).await;
+    assert_eq!(subscription.wait_for_slot(WAIT).await, Some(300));
+
+    ws.close(None).await.unwrap();
+    drop(ws);
+    // Nothing arrives while the socket is down, so the stream polls
+    assert_eq!(subscription.wait_for_slot(Duration::from_millis(200)).await, None);
+    assert!(!subscription.is_connected());
+
+    let (mut ws, _) = accept_subscriber(&listener).await;
+    notify(&mut ws, "slotNotification", json!({ "parent": 309, "root": 278, "slot": 310 })).await;
+    assert_eq!(subscription.wait_for_slot(WAIT).await, Some(310));
+    assert!(subscription.is_connected());
+}
+
+#[tokio::test]
+async fn test_unreachable_endpoint_stays_disconnected() {
+    let (listener, url) = mock_server().await;
+    drop(listener);
+    let mut subscription = SlotSubscription::spawn(&url, SubscriptionKind::Slot, CommitmentConfig::finalized());
+
+    assert_eq!(subscription.wait_for_slot(Duration::from_millis(200)).await, None);
+    assert!(!subscription.is_connected());
+}
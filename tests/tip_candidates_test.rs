@@ -0,0 +1,222 @@
+use solana_block_fetcher::analyzer::registry::{Analyzer, BlockContext};
+use solana_block_fetcher::analyzer::tip_candidates::{
+    TipAccountCandidateRow, TipCandidateAnalyzer, TipCandidateTracker, CANDIDATE_RETENTION_SLOTS, CANDIDATE_WINDOW_SLOTS, MAX_TRACKED_PAYERS,
+    TIP_CANDIDATE_FLUSH_INTERVAL,
+};
+use solana_block_fetcher::analyzer::types::{TipTransfer, Transaction};
+
+fn transfer_tx(payer: &str, recipient: &str, amount: u64, landing_service: Option<&str>) -> Transaction {
+    Transaction {
+        fee_payer: payer.to_string(),
+        tip_transfers: vec![TipTransfer {
+            recipient: recipient.to_string(),
+            amount,
+            via_cpi: 0,
+            landing_service: landing_service.map(str::to_string),
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_recurring_recipient_becomes_candidate() {
+    let mut tracker = TipCandidateTracker::new();
+    for slot in 0..10u64 {
+        // 10 transfers from 5 payers across 10 blocks and 2 validators
+        let payer = format!("payer{}", slot % 5);
+        let validator = if slot % 2 == 0 { "validatorA" } else { "validatorB" };
+        let txs = vec![transfer_tx(&payer, "newTipAccount", 1000 * (slot + 1), None)];
+        tracker.observe_block(&txs, 100 + slot, Some(validator));
+    }
+
+    let candidates = tracker.candidates();
+
+    assert_eq!(candidates.len(), 1);
+    let candidate = &candidates[0];
+    assert_eq!(candidate.recipient, "newTipAccount");
+    assert_eq!(candidate.first_seen_slot, 100);
+    assert_eq!(candidate.last_seen_slot, 109);
+    assert_eq!(candidate.transfer_count, 10);
+    assert_eq!(candidate.distinct_payers, 5);
+    assert_eq!(candidate.total_lamports, 55_000);
+    assert_eq!(candidate.min_tip, 1000);
+    assert_eq!(candidate.p50_tip, 6000);
+    assert_eq!(candidate.max_tip, 10_000);
+    assert_eq!(candidate.validators, vec!["validatorA".to_string(), "validatorB".to_string()]);
+}
+
+#[test]
+fn test_single_payer_and_known_services_are_not_candidates() {
+    let mut tracker = TipCandidateTracker::new();
+    for slot in 0..20u64 {
+        let txs = vec![
+            // The same wallet paying the same merchant repeatedly
+            transfer_tx("payer", "merchant", 5000, None),
+            // Already known tip accounts are not tracked
+            transfer_tx(&format!("payer{}", slot), "jitoTip", 5000, Some("Jito")),
+        ];
+        tracker.observe_block(&txs, slot, None);
+    }
+
+    assert!(tracker.candidates().is_empty());
+    assert_eq!(tracker.tracked_recipients(), 1);
+}
+
+#[test]
+fn test_prune_forgets_quiet_recipients() {
+    let mut tracker = TipCandidateTracker::new();
+    tracker.observe_block(&[transfer_tx("payer", "oneOff", 5000, None)], 10, None);
+
+    tracker.prune(10 + CANDIDATE_WINDOW_SLOTS);
+    assert_eq!(tracker.tracked_recipients(), 1);
+
+    tracker.prune(11 + CANDIDATE_WINDOW_SLOTS);
+    assert_eq!(tracker.tracked_recipients(), 0);
+}
+
+fn observe_candidate(tracker: &mut TipCandidateTracker, recipient: &str, first_slot: u64) {
+    for slot in first_slot..first_slot + 10 {
+        let txs = vec![transfer_tx(&format!("payer{}", slot % 5), recipient, 1000, None)];
+        tracker.observe_block(&txs, slot, None);
+    }
+}
+
+#[test]
+fn test_prune_evicts_quiet_candidates_after_retention() {
+    let mut tracker = TipCandidateTracker::new();
+    observe_candidate(&mut tracker, "candidate", 0);
+    assert_eq!(tracker.candidates().len(), 1);
+
+    // Candidates outlive the window for other recipients...
+    tracker.prune(10 + CANDIDATE_WINDOW_SLOTS);
+    assert_eq!(tracker.tracked_recipients(), 1);
+
+    // ...but not the retention window
+    tracker.prune(10 + CANDIDATE_RETENTION_SLOTS);
+    assert_eq!(tracker.tracked_recipients(), 0);
+}
+
+#[test]
+fn test_slot_is_only_counted_once() {
+    let mut tracker = TipCandidateTracker::new();
+    let txs = vec![transfer_tx("payer", "recipient", 1000, None)];
+
+    assert!(tracker.observe_block(&txs, 100, None));
+    // The same slot analyzed again, e.g. by a retry or an audit, adds nothing
+    assert!(!tracker.observe_block(&txs, 100, None));
+
+    // Edge case: slots older than the pruned window can no longer be told apart from counted ones
+    tracker.prune(100 + CANDIDATE_RETENTION_SLOTS + 1);
+    assert!(!tracker.observe_block(&txs, 50, None));
+    assert!(tracker.observe_block(&txs, 100 + CANDIDATE_RETENTION_SLOTS + 1, None));
+}
+
+#[test]
+fn test_payers_and_amounts_are_bounded() {
+    let mut tracker = TipCandidateTracker::new();
+    // 20k transfers of 1..=20k lamports from 20k different payers
+    for slot in 0..200u64 {
+        let txs: Vec<Transaction> = (0..100u64)
+            .map(|i| {
+                let n = slot * 100 + i + 1;
+                transfer_tx(&format!("payer{}", n), "busyRecipient", n, None)
+            })
+            .collect();
+        tracker.observe_block(&txs, slot, None);
+    }
+
+    let candidate = &tracker.candidates()[0];
+    assert_eq!(candidate.transfer_count, 20_000);
+    assert_eq!(candidate.distinct_payers, MAX_TRACKED_PAYERS as u32);
+    // Min and max are exact, percentiles come from a uniform sample of every transfer
+    assert_eq!(candidate.min_tip, 1);
+    assert_eq!(candidate.max_tip, 20_000);
+    assert!((9_000..=11_000).contains(&candidate.p50_tip), "p50 {} is not near the median", candidate.p50_tip);
+}
+
+#[test]
+fn test_analyzer_ignores_slots_analyzed_again() {
+    let analyzer = TipCandidateAnalyzer::new();
+    let mut slot_txs = Vec::new();
+    for slot in 0..10u64 {
+        slot_txs.push((TIP_CANDIDATE_FLUSH_INTERVAL + slot, vec![transfer_tx(&format!("payer{}", slot % 5), "newTipAccount", 1000, None)]));
+    }
+    let block = |slot: u64, transactions: &[Transaction]| {
+        analyzer
+            .analyze(&BlockContext { slot, blockhash: "hash", block_time: 0, transactions, validator_key: None })
+            .len()
+    };
+
+    // The first block flushes, before the recipient is a candidate
+    for (slot, txs) in &slot_txs {
+        assert_eq!(block(*slot, txs), 0);
+        // Analyzing each block twice must not count its transfers twice
+        assert_eq!(block(*slot, txs), 0);
+    }
+
+    let next_flush = 2 * TIP_CANDIDATE_FLUSH_INTERVAL;
+    let rows = analyzer.analyze(&BlockContext { slot: next_flush, blockhash: "hash", block_time: 0, transactions: &[], validator_key: None });
+    let rows = rows.to_json().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["transfer_count"], 10);
+}
+
+fn stored_candidate() -> TipAccountCandidateRow {
+    TipAccountCandidateRow {
+        recipient: "newTipAccount".to_string(),
+        first_seen_slot: 100,
+        last_seen_slot: 500,
+        transfer_count: 1000,
+        distinct_payers: 40,
+        total_lamports: 5_000_000,
+        min_tip: 1000,
+        p50_tip: 5000,
+        p90_tip: 9000,
+        max_tip: 20_000,
+        validator_count: 1,
+        validators: vec!["validatorA".to_string()],
+    }
+}
+
+#[test]
+fn test_restored_tracker_continues_the_stored_history() {
+    let mut tracker = TipCandidateTracker::new();
+    tracker.restore(&[stored_candidate()]);
+
+    // Before any new transfer the stored row is reproduced
+    let candidates = tracker.candidates();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].transfer_count, 1000);
+    assert_eq!(candidates[0].distinct_payers, 40);
+    assert_eq!(candidates[0].p50_tip, 5000);
+    assert_eq!(candidates[0].p90_tip, 9000);
+
+    // Slots up to the newest stored transfer may already be counted
+    let txs = vec![transfer_tx("payerA", "newTipAccount", 3000, None)];
+    assert!(!tracker.observe_block(&txs, 500, Some("validatorB")));
+    assert!(tracker.observe_block(&txs, 501, Some("validatorB")));
+
+    let candidate = &tracker.candidates()[0];
+    assert_eq!(candidate.first_seen_slot, 100);
+    assert_eq!(candidate.last_seen_slot, 501);
+    assert_eq!(candidate.transfer_count, 1001);
+    assert_eq!(candidate.total_lamports, 5_003_000);
+    // Edge case: payers from before the restart are only known by count, so a new one may not add to it
+    assert_eq!(candidate.distinct_payers, 40);
+    assert_eq!(candidate.validators, vec!["validatorA".to_string(), "validatorB".to_string()]);
+}
+
+#[test]
+fn test_analyzer_restores_from_stored_rows() {
+    let analyzer = TipCandidateAnalyzer::new();
+    assert!(analyzer.is_stateful());
+    analyzer.restore(&vec![stored_candidate()]).unwrap();
+
+    let rows = analyzer.analyze(&BlockContext { slot: 600, blockhash: "hash", block_time: 0, transactions: &[], validator_key: None });
+    let rows = rows.to_json().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["transfer_count"], 1000);
+
+    // Rows of another table are rejected
+    assert!(analyzer.restore(&vec![1u64]).is_err());
+}
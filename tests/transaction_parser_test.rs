@@ -0,0 +1,284 @@
+use std::str::FromStr;
+use serde_json::json;
+use solana_block_fetcher::core::transaction_parser::{
+    decode_compute_unit_limit, decode_compute_unit_price, decode_system_transfer, parse_transactions, PLAIN_TRANSFER,
+};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::system_transaction;
+use solana_sdk::transaction::Transaction as SolanaTransaction;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::{Encodable, EncodedTransactionWithStatusMeta, UiTransactionEncoding};
+
+const JUPITER: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+const COMPUTE_BUDGET: &str = "ComputeBudget111111111111111111111111111111";
+const SYSTEM: &str = "11111111111111111111111111111111";
+
+fn raw_v0_transaction() -> EncodedTransactionWithStatusMeta {
+    serde_json::from_value(json!({
+        "transaction": {
+            "signatures": ["5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"],
+            "message": {
+                "header": {
+                    "numRequiredSignatures": 1,
+                    "numReadonlySignedAccounts": 0,
+                    "numReadonlyUnsignedAccounts": 1
+                },
+                "accountKeys": [
+                    "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+                    COMPUTE_BUDGET
+                ],
+                "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                "instructions": [
+                    { "programIdIndex": 1, "accounts": [], "data": "3DTZbgwsozUF", "stackHeight": null },
+                    // Index 3 is the readonly address loaded from the lookup table
+                    { "programIdIndex": 3, "accounts": [0, 2], "data": "", "stackHeight": null }
+                ],
+                "addressTableLookups": [{
+                    "accountKey": "2immgwYNHBbyVQKVGCEkgWpi53bLwWNRMB5G2nbgYV17",
+                    "writableIndexes": [0],
+                    "readonlyIndexes": [1]
+                }]
+            }
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+            "computeUnitsConsumed": 61_234,
+            "loadedAddresses": {
+                "writable": ["6GkAFp1Q3YxT1pZLwEY2MgDKQd8KnfyqBb6LBpJzCSZo"],
+                "readonly": [JUPITER]
+            }
+        },
+        "version": 0
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_raw_message_resolves_program_ids_through_lookup_tables() {
+    let transactions = parse_transactions(&[raw_v0_transaction()], 1);
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].program_ids, vec![COMPUTE_BUDGET.to_string(), JUPITER.to_string()]);
+    assert_eq!(transactions[0].has_compute_budget, 1);
+    assert_eq!(transactions[0].compute_units_consumed, 61_234);
+}
+
+#[test]
+fn test_unresolvable_program_index_is_skipped() {
+    let mut tx = raw_v0_transaction();
+    // Without the loaded addresses index 3 points past the account keys
+    tx.meta.as_mut().unwrap().loaded_addresses = OptionSerializer::None;
+
+    let transactions = parse_transactions(&[tx], 1);
+
+    assert_eq!(transactions[0].program_ids, vec![COMPUTE_BUDGET.to_string()]);
+}
+
+#[test]
+fn test_parsed_message_resolves_compiled_instructions() {
+    let tx: EncodedTransactionWithStatusMeta = serde_json::from_value(json!({
+        "transaction": {
+            "signatures": ["5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"],
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", "writable": true, "signer": true, "source": "transaction" },
+                    { "pubkey": SYSTEM, "writable": false, "signer": false, "source": "transaction" },
+                    { "pubkey": JUPITER, "writable": false, "signer": false, "source": "lookupTable" }
+                ],
+                "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                "instructions": [
+                    { "programIdIndex": 2, "accounts": [0], "data": "", "stackHeight": null }
+                ]
+            }
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": []
+        },
+        "version": 0
+    }))
+    .unwrap();
+
+    let transactions = parse_transactions(&[tx], 1);
+
+    assert_eq!(transactions[0].program_ids, vec![JUPITER.to_string()]);
+}
+
+#[test]
+fn test_base64_transaction_is_decoded() {
+    let payer = Keypair::new();
+    let jito_tip_account = Pubkey::from_str("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5").unwrap();
+    let transfer = system_transaction::transfer(&payer, &jito_tip_account, 10_000, Hash::new_unique());
+    let tx = EncodedTransactionWithStatusMeta {
+        transaction: transfer.encode(UiTransactionEncoding::Base64),
+        meta: None,
+        version: None,
+    };
+
+    let transactions = parse_transactions(&[tx], 1);
+
+    assert_eq!(transactions[0].signature, transfer.signatures[0].to_string());
+    assert_eq!(transactions[0].recent_blockhash, transfer.message.recent_blockhash.to_string());
+    assert_eq!(transactions[0].program_ids, vec![SYSTEM.to_string()]);
+    assert_eq!(transactions[0].landing_service, "Jito");
+    assert_eq!(transactions[0].tip_amount, 10_000);
+    assert_eq!(transactions[0].tip_via_cpi, 0);
+}
+
+#[test]
+fn test_decode_system_transfer() {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&5_000u64.to_le_bytes());
+    assert_eq!(decode_system_transfer(&data), Some(5_000));
+
+    // CreateAccount (variant 0) is not a transfer
+    data[0] = 0;
+    assert_eq!(decode_system_transfer(&data), None);
+    assert_eq!(decode_system_transfer(&[2, 0, 0, 0]), None);
+}
+
+#[test]
+fn test_fee_decomposed_from_compute_budget_instructions() {
+    let payer = Keypair::new();
+    let instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+        ComputeBudgetInstruction::set_compute_unit_price(50_000),
+        system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1),
+    ];
+    let transaction = SolanaTransaction::new_signed_with_payer(&instructions, Some(&payer.pubkey()), &[&payer], Hash::new_unique());
+    let mut tx: EncodedTransactionWithStatusMeta = serde_json::from_value(json!({
+        "transaction": ["", "base64"],
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            // 5000 base fee + 200_000 CU * 50_000 micro-lamports
+            "fee": 15_000,
+            "preBalances": [],
+            "postBalances": []
+        }
+    }))
+    .unwrap();
+    tx.transaction = transaction.encode(UiTransactionEncoding::Base64);
+
+    let transactions = parse_transactions(&[tx], 1);
+
+    assert_eq!(transactions[0].base_fee, 5000);
+    assert_eq!(transactions[0].priority_fee, 10_000);
+    assert_eq!(transactions[0].compute_unit_limit, 200_000);
+    assert_eq!(transactions[0].compute_unit_price, 50_000);
+}
+
+#[test]
+fn test_decode_compute_budget_instructions() {
+    assert_eq!(decode_compute_unit_limit(&ComputeBudgetInstruction::set_compute_unit_limit(1_400_000).data), Some(1_400_000));
+    assert_eq!(decode_compute_unit_price(&ComputeBudgetInstruction::set_compute_unit_price(1_000).data), Some(1_000));
+
+    // Edge case: a limit is not a price, and heap frame requests are neither
+    assert_eq!(decode_compute_unit_price(&ComputeBudgetInstruction::set_compute_unit_limit(1_000).data), None);
+    assert_eq!(decode_compute_unit_limit(&ComputeBudgetInstruction::request_heap_frame(32 * 1024).data), None);
+}
+
+#[test]
+fn test_tip_paid_through_cpi() {
+    let jito_tip = "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5";
+    let tx: EncodedTransactionWithStatusMeta = serde_json::from_value(json!({
+        "transaction": {
+            "signatures": ["5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"],
+            "message": {
+                "accountKeys": [
+                    { "pubkey": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", "writable": true, "signer": true, "source": "transaction" },
+                    { "pubkey": jito_tip, "writable": true, "signer": false, "source": "transaction" },
+                    { "pubkey": SYSTEM, "writable": false, "signer": false, "source": "transaction" },
+                    { "pubkey": JUPITER, "writable": false, "signer": false, "source": "transaction" }
+                ],
+                "recentBlockhash": "EkSnNWid2cvwEVnVx9aBqawnmiCNiDgp3gUdkDPTKN1N",
+                "instructions": [
+                    { "programId": JUPITER, "accounts": ["9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", jito_tip], "data": "", "stackHeight": null }
+                ]
+            }
+        },
+        "meta": {
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5000,
+            "preBalances": [],
+            "postBalances": [],
+            "innerInstructions": [{
+                "index": 0,
+                "instructions": [{
+                    "program": "system",
+                    "programId": SYSTEM,
+                    "parsed": {
+                        "type": "transfer",
+                        "info": { "source": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", "destination": jito_tip, "lamports": 25_000 }
+                    },
+                    "stackHeight": 2
+                }]
+            }]
+        },
+        "version": 0
+    }))
+    .unwrap();
+
+    let transactions = parse_transactions(&[tx], 1);
+
+    assert_eq!(transactions[0].landing_service, "Jito");
+    assert_eq!(transactions[0].tip_recipient, jito_tip);
+    assert_eq!(transactions[0].tip_amount, 25_000);
+    assert_eq!(transactions[0].tip_via_cpi, 1);
+    // Inner instructions do not add to the transaction's programs
+    assert_eq!(transactions[0].program_ids, vec![JUPITER.to_string()]);
+}
+
+fn base64_transfers(transfers: &[(Pubkey, u64)]) -> EncodedTransactionWithStatusMeta {
+    let payer = Keypair::new();
+    let instructions: Vec<_> = transfers
+        .iter()
+        .map(|(to, lamports)| system_instruction::transfer(&payer.pubkey(), to, *lamports))
+        .collect();
+    let transaction = SolanaTransaction::new_signed_with_payer(&instructions, Some(&payer.pubkey()), &[&payer], Hash::new_unique());
+    EncodedTransactionWithStatusMeta {
+        transaction: transaction.encode(UiTransactionEncoding::Base64),
+        meta: None,
+        version: None,
+    }
+}
+
+#[test]
+fn test_known_tip_account_takes_precedence_over_earlier_payment() {
+    let merchant = Pubkey::new_unique();
+    let jito_tip_account = Pubkey::from_str("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5").unwrap();
+
+    let transactions = parse_transactions(&[base64_transfers(&[(merchant, 2_000_000_000), (jito_tip_account, 10_000)])], 1);
+
+    assert_eq!(transactions[0].landing_service, "Jito");
+    assert_eq!(transactions[0].tip_recipient, jito_tip_account.to_string());
+    assert_eq!(transactions[0].tip_amount, 10_000);
+    // Both transfers are kept as candidates
+    assert_eq!(transactions[0].tip_transfers.len(), 2);
+    assert_eq!(transactions[0].tip_transfers[0].recipient, merchant.to_string());
+    assert_eq!(transactions[0].tip_transfers[0].landing_service, None);
+}
+
+#[test]
+fn test_plain_transfer_is_not_a_tip() {
+    let merchant = Pubkey::new_unique();
+
+    let transactions = parse_transactions(&[base64_transfers(&[(merchant, 2_000_000_000)])], 1);
+
+    assert_eq!(transactions[0].landing_service, PLAIN_TRANSFER);
+    assert_eq!(transactions[0].tip_recipient, "");
+    assert_eq!(transactions[0].tip_amount, 0);
+    assert_eq!(transactions[0].tip_transfers.len(), 1);
+}
@@ -0,0 +1,46 @@
+use solana_block_fetcher::analyzer::transactions::analyze_transactions;
+use solana_block_fetcher::analyzer::types::Transaction;
+
+#[test]
+fn test_transaction_rows_keep_per_transaction_detail() {
+    let transactions = vec![
+        Transaction {
+            slot: 1000,
+            position: 1,
+            signature: "sig1".to_string(),
+            fee_payer: "payer1".to_string(),
+            fee: 25000,
+            base_fee: 5000,
+            priority_fee: 20000,
+            compute_units_consumed: 150_000,
+            program_ids: vec![
+                "ComputeBudget111111111111111111111111111111".to_string(),
+                "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4".to_string(),
+            ],
+            landing_service: "Jito".to_string(),
+            tip_recipient: "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5".to_string(),
+            tip_amount: 10000,
+            ..Default::default()
+        },
+        Transaction { slot: 1000, position: 2, signature: "sig2".to_string(), failed: 1, ..Default::default() },
+    ];
+
+    let rows = analyze_transactions(&transactions, 1234567890);
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].position, 1);
+    assert_eq!(rows[0].block_time, 1234567890);
+    assert_eq!(rows[0].fee_payer, "payer1");
+    assert_eq!(rows[0].priority_fee, 20000);
+    assert_eq!(rows[0].compute_units_consumed, 150_000);
+    assert_eq!(rows[0].primary_program, "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+    assert_eq!(rows[0].program_name, "Jupiter");
+    assert_eq!(rows[0].transaction_type, "jupiter");
+    assert_eq!(rows[0].landing_service, "Jito");
+    assert_eq!(rows[0].tip_amount, 10000);
+
+    // Edge case: a transaction without programs has no primary program
+    assert_eq!(rows[1].primary_program, "");
+    assert_eq!(rows[1].program_name, "");
+    assert_eq!(rows[1].failed, 1);
+}